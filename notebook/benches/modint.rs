@@ -0,0 +1,53 @@
+// Zn vs MontZn vs DynZn on multiplication heavy loops, all modulo 998244353.
+// Run with `cargo bench --bench modint`.
+use {
+    std::time::Instant,
+    notebook::{
+        dyn_zn::DynZn,
+        montgomery::MontZn,
+        zn::{ModInt, Zn},
+    },
+};
+
+const MOD: u32 = 998244353;
+const N: usize = 10_000;
+const STEPS: usize = 100_000_000;
+
+/// Linear recurrence x = x * a + b, every step depends on the previous one.
+fn recurrence<T: ModInt>() -> u64 {
+    let (a, b) = (T::from(123456789), T::from(987654321));
+    let mut x = T::from(1);
+    for _ in 0..STEPS {
+        x = x * a + b;
+    }
+    x.value()
+}
+
+/// Naive convolution of two sequences of length N, independent products.
+fn convolution<T: ModInt>() -> u64 {
+    let f: Vec<T> = (0..N as i64).map(|i| T::from(i * i + 1)).collect();
+    let g: Vec<T> = (0..N as i64).map(|i| T::from(3 * i + 7)).collect();
+    let mut h = [T::default(); 2 * N - 1];
+    for (i, &x) in f.iter().enumerate() {
+        for (j, &y) in g.iter().enumerate() {
+            h[i + j] += x * y;
+        }
+    }
+    h.iter().map(|x| x.value()).sum()
+}
+
+fn bench<T: ModInt>(name: &str) {
+    let start = Instant::now();
+    let checksum = recurrence::<T>();
+    print!("{:<7} recurrence {:>5}ms (checksum {})", name, start.elapsed().as_millis(), checksum);
+    let start = Instant::now();
+    let checksum = convolution::<T>();
+    println!(", convolution {:>5}ms (checksum {})", start.elapsed().as_millis(), checksum);
+}
+
+fn main() {
+    DynZn::set_modulus(MOD);
+    bench::<Zn<MOD>>("Zn");
+    bench::<MontZn<MOD>>("MontZn");
+    bench::<DynZn>("DynZn");
+}
@@ -0,0 +1,50 @@
+// Recursive SegTree vs FlatSegTree on N point updates and range queries.
+// Run with `cargo bench --bench segment_tree`.
+use {
+    std::time::Instant,
+    notebook::{
+        flat_segment_tree::FlatSegTree,
+        segment_tree::{SegTree, Sum},
+    },
+};
+
+const N: usize = 1_000_000;
+const OPS: usize = 1_000_000;
+
+/// xorshift, deterministic so both trees see the same operations.
+fn operations() -> Vec<(usize, usize, i64)> {
+    let mut x: u64 = 88172645463325252;
+    let mut next = move || {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x
+    };
+    (0..OPS)
+        .map(|_| {
+            let a = next() as usize % N;
+            let b = next() as usize % N;
+            (a.min(b), a.max(b), (next() % 1000) as i64)
+        })
+        .collect()
+}
+
+fn main() {
+    let ops = operations();
+
+    let start = Instant::now();
+    let mut stree = SegTree::<Sum<i64>>::new(0, N - 1, 0);
+    let mut checksum = 0;
+    for (i, &(a, b, v)) in ops.iter().enumerate() {
+        if i % 2 == 0 { stree.update(a, v); } else { checksum += stree.query(a, b); }
+    }
+    println!("SegTree:     {:>5}ms (checksum {})", start.elapsed().as_millis(), checksum);
+
+    let start = Instant::now();
+    let mut stree = FlatSegTree::<Sum<i64>>::new(0, N - 1, 0);
+    let mut checksum = 0;
+    for (i, &(a, b, v)) in ops.iter().enumerate() {
+        if i % 2 == 0 { stree.update(a, v); } else { checksum += stree.query(a, b); }
+    }
+    println!("FlatSegTree: {:>5}ms (checksum {})", start.elapsed().as_millis(), checksum);
+}
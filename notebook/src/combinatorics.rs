@@ -0,0 +1,174 @@
+// ----- Combinatorics modulo a prime -----
+// Factorials and inverse factorials up to n in O(n) with a single inverse,
+// then binomials and friends in O(1). Works with any ModInt, e.g. Zn<998244353>.
+use crate::zn::ModInt;
+
+pub struct Combinatorics<T: ModInt> {
+    fact: Vec<T>,
+    inv_fact: Vec<T>,
+}
+
+impl<T: ModInt> Combinatorics<T> {
+    /// Tables for [0, n], n must be smaller than the modulus.
+    pub fn new(n: usize) -> Combinatorics<T> {
+        debug_assert!((n as u64) < T::modulus());
+        let mut fact = vec![T::from(1); n + 1];
+        for i in 1..=n {
+            fact[i] = fact[i - 1] * T::from(i as i64);
+        }
+        // 1/(i-1)! = i * 1/i!
+        let mut inv_fact = vec![T::from(1); n + 1];
+        inv_fact[n] = fact[n].inverse();
+        for i in (1..=n).rev() {
+            inv_fact[i - 1] = inv_fact[i] * T::from(i as i64);
+        }
+        Combinatorics { fact, inv_fact }
+    }
+
+    pub fn fact(&self, n: usize) -> T {
+        self.fact[n]
+    }
+
+    pub fn inv_fact(&self, n: usize) -> T {
+        self.inv_fact[n]
+    }
+
+    /// 1/n, for n >= 1.
+    pub fn inv(&self, n: usize) -> T {
+        debug_assert!(n >= 1);
+        self.inv_fact[n] * self.fact[n - 1]
+    }
+
+    /// Ways to choose r out of n, 0 if r > n.
+    pub fn ncr(&self, n: usize, r: usize) -> T {
+        if r > n { return T::default(); }
+        self.fact[n] * self.inv_fact[r] * self.inv_fact[n - r]
+    }
+
+    /// Ordered ways to choose r out of n, 0 if r > n.
+    pub fn npr(&self, n: usize, r: usize) -> T {
+        if r > n { return T::default(); }
+        self.fact[n] * self.inv_fact[n - r]
+    }
+
+    /// Ways to split sum(k) items into groups of sizes k[0], k[1], ...
+    pub fn multinomial(&self, k: &[usize]) -> T {
+        k.iter().fold(self.fact[k.iter().sum::<usize>()], |acc, &ki| acc * self.inv_fact[ki])
+    }
+
+    /// Ways to pick a multiset of r out of n kinds: C(n + r - 1, r).
+    pub fn multichoose(&self, n: usize, r: usize) -> T {
+        if n == 0 { return if r == 0 { T::from(1) } else { T::default() }; }
+        self.ncr(n + r - 1, r)
+    }
+
+    /// C(2n, n) / (n + 1): balanced parentheses, binary trees with n nodes, ...
+    pub fn catalan(&self, n: usize) -> T {
+        self.fact[2 * n] * self.inv_fact[n + 1] * self.inv_fact[n]
+    }
+
+    /// C(n, r) for huge n and r by Lucas' theorem, multiplying C(n_i, r_i) over
+    /// the base p digits. Needs the modulus p prime and tables up to p - 1.
+    pub fn ncr_lucas(&self, mut n: u64, mut r: u64) -> T {
+        let p = T::modulus();
+        debug_assert!(self.fact.len() as u64 >= p);
+        let mut result = T::from(1);
+        while r > 0 {
+            result *= self.ncr((n % p) as usize, (r % p) as usize);
+            n /= p;
+            r /= p;
+        }
+        result
+    }
+}
+
+/// Unsigned Stirling numbers of the first kind for [0, n]: s[i][k] is the
+/// number of permutations of i elements with k cycles. O(n^2).
+pub fn stirling1<T: ModInt>(n: usize) -> Vec<Vec<T>> {
+    let mut s = vec![vec![T::default(); n + 1]; n + 1];
+    s[0][0] = T::from(1);
+    for i in 1..=n {
+        for k in 1..=i {
+            s[i][k] = s[i - 1][k - 1] + T::from(i as i64 - 1) * s[i - 1][k];
+        }
+    }
+    s
+}
+
+/// Stirling numbers of the second kind for [0, n]: s[i][k] is the number of
+/// ways to split i elements into k non empty sets. O(n^2).
+pub fn stirling2<T: ModInt>(n: usize) -> Vec<Vec<T>> {
+    let mut s = vec![vec![T::default(); n + 1]; n + 1];
+    s[0][0] = T::from(1);
+    for i in 1..=n {
+        for k in 1..=i {
+            s[i][k] = s[i - 1][k - 1] + T::from(k as i64) * s[i - 1][k];
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zn::Zn;
+
+    type Z = Zn<1000000007>;
+
+    #[test]
+    fn test_combinatorics() {
+        let c = Combinatorics::<Z>::new(100);
+        // Pascal's triangle
+        let mut row = vec![1u64];
+        for n in 0..60 {
+            for (r, &x) in row.iter().enumerate() {
+                assert_eq!(c.ncr(n, r), Z::from(x));
+            }
+            row = (0..=row.len()).map(|r| if r == 0 || r == row.len() { 1 } else { row[r - 1] + row[r] }).collect();
+        }
+        assert_eq!(c.ncr(3, 5), Z::new(0));
+        assert_eq!(c.npr(5, 2), Z::new(20));
+        assert_eq!(c.npr(5, 6), Z::new(0));
+        assert_eq!(c.fact(20), Z::from(2432902008176640000u64));
+        assert_eq!(c.fact(7) * c.inv_fact(7), Z::new(1));
+        assert_eq!(c.inv(7) * Z::new(7), Z::new(1));
+        // MISSISSIPPI
+        assert_eq!(c.multinomial(&[1, 4, 4, 2]), Z::new(34650));
+        assert_eq!(c.multichoose(3, 2), Z::new(6));
+        assert_eq!(c.multichoose(0, 0), Z::new(1));
+        let catalan: Vec<Z> = (0..10).map(|n| c.catalan(n)).collect();
+        assert_eq!(catalan, [1, 1, 2, 5, 14, 42, 132, 429, 1430, 4862].map(Z::new));
+    }
+
+    #[test]
+    fn test_lucas() {
+        type Z7 = Zn<7>;
+        let c = Combinatorics::<Z7>::new(6);
+        // C(n, r) < 10^9 + 7 for n < 30, so these are the exact values
+        let exact = Combinatorics::<Z>::new(30);
+        for n in 0..30 {
+            for r in 0..=n + 1 {
+                let expected = Z7::from(exact.ncr(n, r).value());
+                assert_eq!(c.ncr_lucas(n as u64, r as u64), expected);
+            }
+        }
+        // digits (1, 0, ..., 0, 3) choose (0, ..., 1, ..., 0, 1) and (1, 0, ..., 0, 1)
+        assert_eq!(c.ncr_lucas(7u64.pow(20) + 3, 7u64.pow(10) + 1), Z7::new(0));
+        assert_eq!(c.ncr_lucas(7u64.pow(20) + 3, 7u64.pow(20) + 1), Z7::new(3));
+    }
+
+    #[test]
+    fn test_stirling() {
+        let s1 = stirling1::<Z>(6);
+        let s2 = stirling2::<Z>(6);
+        assert_eq!(s1[4][..=4], [0, 6, 11, 6, 1].map(Z::new));
+        assert_eq!(s2[5][..=5], [0, 1, 15, 25, 10, 1].map(Z::new));
+        let c = Combinatorics::<Z>::new(6);
+        for n in 0..=6 {
+            // every permutation has some number of cycles
+            assert_eq!((0..=n).map(|k| s1[n][k]).sum::<Z>(), c.fact(n));
+            // Bell numbers
+            assert_eq!((0..=n).map(|k| s2[n][k]).sum::<Z>(), Z::new([1, 1, 2, 5, 15, 52, 203][n]));
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// ----- Chinese remainder theorem -----
+// crt merges x = r_i mod m_i for any moduli, coprime or not, into a single
+// x = r mod lcm(m_i). garner rebuilds x mod another modulus when the moduli are
+// coprime but their product is too big for an i64, e.g. convolutions modulo
+// several NTT primes. Products go through i128.
+use crate::zn::{extended_gcd, mod_inverse};
+
+/// (r, m) such that x = r_i mod m_i for all i iff x = r mod m, with r in [0, m)
+/// and m = lcm(m_i), or None if there is no such x. The m_i must be >= 1 and
+/// their lcm must fit in an i64. An empty list gives (0, 1).
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let (mut r, mut m) = (0i64, 1i64);
+    for &(ri, mi) in congruences {
+        debug_assert!(mi >= 1);
+        let ri = ri.rem_euclid(mi);
+        // m p + mi q = g, then x = r + m t with m t = ri - r mod mi
+        let (g, p, _) = extended_gcd(m, mi);
+        if (ri - r) % g != 0 { return None; }
+        let mi_g = (mi / g) as i128;
+        let t = ((ri - r) / g) as i128 % mi_g * p as i128 % mi_g;
+        let lcm = m as i128 * mi_g;
+        r = (r as i128 + m as i128 * t).rem_euclid(lcm) as i64;
+        m = i64::try_from(lcm).expect("lcm of the moduli doesn't fit in an i64");
+    }
+    Some((r, m))
+}
+
+/// x mod modulus for the x in [0, m_1 m_2 ... m_k) with x = r_i mod m_i, in
+/// O(k^2). x = t_1 + t_2 m_1 + t_3 m_1 m_2 + ..., finding each t_i mod m_i in
+/// turn. None if the m_i aren't pairwise coprime.
+pub fn garner(congruences: &[(i64, i64)], modulus: i64) -> Option<i64> {
+    let k = congruences.len();
+    let moduli: Vec<i128> = congruences.iter().map(|&(_, m)| m as i128).chain([modulus as i128]).collect();
+    // prefix[j] = m_1 ... m_i mod moduli[j], value[j] = t_1 + ... + t_i m_1 ... m_(i-1) mod moduli[j]
+    let mut prefix = vec![1i128; k + 1];
+    let mut value = vec![0i128; k + 1];
+    for (i, &(r, m)) in congruences.iter().enumerate() {
+        let inv = mod_inverse(prefix[i] as i64, m)? as i128;
+        let t = (r as i128 - value[i]).rem_euclid(m as i128) * inv % m as i128;
+        for j in i + 1..=k {
+            value[j] = (value[j] + t * prefix[j]) % moduli[j];
+            prefix[j] = prefix[j] * m as i128 % moduli[j];
+        }
+    }
+    Some(value[k] as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        // Against a search over [0, m1 m2) for all small moduli and remainders
+        for m1 in 1..13i64 {
+            for m2 in 1..13i64 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..m1 * m2).find(|x| x % m1 == r1 && x % m2 == r2);
+                        let lcm = m1 * m2 / extended_gcd(m1, m2).0;
+                        assert_eq!(crt(&[(r1, m1), (r2, m2)]), expected.map(|x| (x, lcm)));
+                    }
+                }
+            }
+        }
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 4), (1, 6)]), Some((7, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        // lcm close to 10^18, the products only fit in an i128
+        let (p, q) = (999999937, 999999929);
+        assert_eq!(crt(&[(p - 1, p), (q - 1, q)]), Some((p * q - 1, p * q)));
+        assert_eq!(crt(&[(5, 1_000_000_000_000_000_000), (4, 2)]), None);
+    }
+
+    #[test]
+    fn test_garner() {
+        let moduli = [754974721i64, 167772161, 469762049];
+        let x: i128 = 12345678901234567890123456; // below m_1 m_2 m_3 ~ 5.9 * 10^25
+        let congruences: Vec<(i64, i64)> = moduli.iter().map(|&m| ((x % m as i128) as i64, m)).collect();
+        assert_eq!(garner(&congruences, 1_000_000_007), Some((x % 1_000_000_007) as i64));
+        assert_eq!(garner(&congruences, i64::MAX), Some((x % i64::MAX as i128) as i64));
+        assert_eq!(garner(&[(2, 3), (3, 5), (2, 7)], 1000), Some(23));
+        assert_eq!(garner(&[(2, 3), (3, 5), (2, 7)], 10), Some(3));
+        assert_eq!(garner(&[], 10), Some(0));
+        assert_eq!(garner(&[(1, 4), (1, 6)], 10), None);
+        for m in 1..20 {
+            assert_eq!(garner(&[(4, 5), (6, 7)], m), Some(34 % m));
+        }
+    }
+}
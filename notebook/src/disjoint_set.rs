@@ -1,7 +1,13 @@
 // ----- Union-Find / Disjoint Set Union (DSU) -----
+use std::ops::{Add, Sub};
+
+#[cfg_attr(feature = "local", derive(Debug))]
 pub struct DSet {
     pub p: Vec<usize>,
     pub r: Vec<usize>,
+    /// Size of the component of each root.
+    pub size: Vec<usize>,
+    pub components: usize,
 }
 
 impl DSet {
@@ -9,6 +15,8 @@ impl DSet {
         DSet {
             p: (0..n).collect(),
             r: vec![0; n],
+            size: vec![1; n],
+            components: n,
         }
     }
 
@@ -16,7 +24,7 @@ impl DSet {
         if self.p[i] != i {
             self.p[i] = self.root(self.p[i]);
         }
-        return self.p[i];
+        self.p[i]
     }
 
     pub fn merge(&mut self, a: usize, b: usize) -> bool {
@@ -27,19 +35,185 @@ impl DSet {
         }
         if self.r[a] > self.r[b] {
             self.p[b] = a;
+            self.size[a] += self.size[b];
         } else {
             self.p[a] = b;
+            self.size[b] += self.size[a];
             if self.r[a] == self.r[b] {
                 self.r[b] += 1;
             }
         }
+        self.components -= 1;
+        true
+    }
+
+    /// Size of the component of i.
+    pub fn size(&mut self, i: usize) -> usize {
+        let root = self.root(i);
+        self.size[root]
+    }
+
+    /// Elements in the component of i, sorted.
+    pub fn members(&mut self, i: usize) -> Vec<usize> {
+        let root = self.root(i);
+        (0..self.p.len()).filter(|&j| self.root(j) == root).collect()
+    }
+
+    /// Every component, each one sorted, ordered by their smallest element.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.p.len()];
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for i in 0..self.p.len() {
+            let root = self.root(i);
+            if index[root] == usize::MAX {
+                index[root] = groups.len();
+                groups.push(Vec::new());
+            }
+            groups[index[root]].push(i);
+        }
+        groups
+    }
+}
+
+/// DSU with a potential x_i for every element, known relative to the others in
+/// its component through constraints like x_a - x_b = d. w[i] is x_i - x_p[i].
+#[cfg_attr(feature = "local", derive(Debug))]
+pub struct WeightedDSet<T> {
+    pub p: Vec<usize>,
+    pub r: Vec<usize>,
+    pub w: Vec<T>,
+}
+
+impl<T: Copy + Default + PartialEq + Add<Output = T> + Sub<Output = T>> WeightedDSet<T> {
+    pub fn new(n: usize) -> WeightedDSet<T> {
+        WeightedDSet {
+            p: (0..n).collect(),
+            r: vec![0; n],
+            w: vec![T::default(); n],
+        }
+    }
+
+    /// Root of i, after which w[i] is x_i - x_root.
+    pub fn root(&mut self, i: usize) -> usize {
+        if self.p[i] != i {
+            let p = self.p[i];
+            let root = self.root(p);
+            self.w[i] = self.w[i] + self.w[p];
+            self.p[i] = root;
+        }
+        self.p[i]
+    }
+
+    /// x_a - x_b, if a and b are in the same component.
+    pub fn diff(&mut self, a: usize, b: usize) -> Option<T> {
+        if self.root(a) != self.root(b) {
+            return None;
+        }
+        Some(self.w[a] - self.w[b])
+    }
+
+    /// Add the constraint x_a - x_b = d. Ok(true) if it merged two components,
+    /// Ok(false) if it was already implied and Err(x_a - x_b) if it contradicts
+    /// the known difference.
+    pub fn merge(&mut self, a: usize, b: usize, d: T) -> Result<bool, T> {
+        let ra = self.root(a);
+        let rb = self.root(b);
+        if ra == rb {
+            let known = self.w[a] - self.w[b];
+            return if known == d { Ok(false) } else { Err(known) };
+        }
+        // x_ra - x_rb = d - w[a] + w[b]
+        let root_diff = d - self.w[a] + self.w[b];
+        if self.r[ra] > self.r[rb] {
+            self.p[rb] = ra;
+            self.w[rb] = T::default() - root_diff;
+        } else {
+            self.p[ra] = rb;
+            self.w[ra] = root_diff;
+            if self.r[ra] == self.r[rb] {
+                self.r[rb] += 1;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// DSU whose merges can be undone, e.g. for offline dynamic connectivity or
+/// divide and conquer. Union by size without path compression, so root is
+/// O(log n) and every merge only changes one parent.
+#[cfg_attr(feature = "local", derive(Debug))]
+pub struct RollbackDSet {
+    pub p: Vec<usize>,
+    pub size: Vec<usize>,
+    pub components: usize,
+    /// Roots attached to another root by each merge, most recent last.
+    history: Vec<usize>,
+}
+
+impl RollbackDSet {
+    pub fn new(n: usize) -> RollbackDSet {
+        RollbackDSet {
+            p: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn root(&self, mut i: usize) -> usize {
+        while self.p[i] != i {
+            i = self.p[i];
+        }
+        i
+    }
+
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.root(a) == self.root(b)
+    }
+
+    /// Size of the component of i.
+    pub fn size(&self, i: usize) -> usize {
+        self.size[self.root(i)]
+    }
+
+    pub fn merge(&mut self, a: usize, b: usize) -> bool {
+        let mut a = self.root(a);
+        let mut b = self.root(b);
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.p[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        self.history.push(b);
         true
     }
+
+    /// State to go back to with rollback, counts the merges done so far.
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undo the merges done after the given snapshot.
+    pub fn rollback(&mut self, snapshot: usize) {
+        debug_assert!(snapshot <= self.history.len());
+        while self.history.len() > snapshot {
+            let b = self.history.pop().unwrap();
+            let a = self.p[b];
+            self.p[b] = b;
+            self.size[a] -= self.size[b];
+            self.components += 1;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::Rng;
 
     #[test]
     fn test_disjoint_set() {
@@ -59,5 +233,66 @@ mod tests {
         assert_eq!(9, ds.root(9));
         let root0 = ds.root(0);
         assert_eq!(ds.r[root0], 2);
+        assert_eq!(ds.size(8), 5);
+        assert_eq!(ds.size(3), 1);
+        assert_eq!(ds.components, 6);
+        assert!(!ds.merge(2, 8));
+        assert_eq!(ds.components, 6);
+        assert_eq!(ds.members(6), vec![0, 2, 4, 6, 8]);
+        assert_eq!(ds.members(7), vec![7]);
+        ds.merge(9, 3);
+        assert_eq!(ds.groups(), vec![vec![0, 2, 4, 6, 8], vec![1], vec![3, 9], vec![5], vec![7]]);
+
+        // x_0 - x_1 = 3, x_2 - x_1 = 5, x_3 - x_4 = -1
+        let mut wds = WeightedDSet::<i64>::new(5);
+        assert_eq!(wds.merge(0, 1, 3), Ok(true));
+        assert_eq!(wds.merge(2, 1, 5), Ok(true));
+        assert_eq!(wds.merge(3, 4, -1), Ok(true));
+        assert_eq!(wds.diff(2, 0), Some(2));
+        assert_eq!(wds.diff(0, 2), Some(-2));
+        assert_eq!(wds.diff(0, 3), None);
+        assert_eq!(wds.merge(0, 2, -2), Ok(false));
+        assert_eq!(wds.merge(2, 0, 1), Err(2));
+        // x_4 - x_0 = 10
+        assert_eq!(wds.merge(4, 0, 10), Ok(true));
+        assert_eq!(wds.diff(3, 2), Some(-1 + 10 - 2));
+        assert_eq!(wds.merge(1, 3, -5), Err(-3 - 9));
+    }
+
+    #[test]
+    fn test_rollback_disjoint_set() {
+        let mut rng = Rng(88172645463325252);
+        let n = 12;
+        let mut ds = RollbackDSet::new(n);
+        // brute force: the component label of every element, one per snapshot
+        let mut labels: Vec<usize> = (0..n).collect();
+        let mut saved = vec![(ds.snapshot(), labels.clone())];
+        for _ in 0..2000 {
+            match rng.next(4) {
+                0 => saved.push((ds.snapshot(), labels.clone())),
+                1 => {
+                    // snapshots taken after the one we go back to are no longer valid
+                    saved.truncate(rng.next(saved.len()) + 1);
+                    let (snapshot, old) = saved.last().unwrap();
+                    ds.rollback(*snapshot);
+                    labels = old.clone();
+                },
+                _ => {
+                    let (a, b) = (rng.next(n), rng.next(n));
+                    let (la, lb) = (labels[a], labels[b]);
+                    assert_eq!(ds.merge(a, b), la != lb);
+                    for l in &mut labels {
+                        if *l == lb { *l = la; }
+                    }
+                },
+            }
+            for a in 0..n {
+                assert_eq!(ds.size(a), labels.iter().filter(|&&l| l == labels[a]).count());
+                for b in 0..n {
+                    assert_eq!(ds.same(a, b), labels[a] == labels[b]);
+                }
+            }
+            assert_eq!(ds.components, (0..n).filter(|&i| labels[i] == i).count());
+        }
     }
 }
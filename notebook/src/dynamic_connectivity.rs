@@ -0,0 +1,156 @@
+// ----- Offline Dynamic Connectivity -----
+// Connectivity queries on a graph whose edges are added and removed over time.
+// Each edge is alive during a range of queries, which is split over the nodes
+// of a segment tree on the queries. A DFS of the tree merges the edges of each
+// node on the way down and rolls them back on the way up, so every query leaf
+// sees exactly the edges alive at its time. O((n + q) log q log n).
+use {
+    std::collections::HashMap,
+    crate::disjoint_set::RollbackDSet,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+    /// Add the undirected edge u - v, parallel edges are fine.
+    Add(usize, usize),
+    /// Remove one copy of the edge u - v, which must be present.
+    Remove(usize, usize),
+    /// Are u and v connected?
+    Query(usize, usize),
+}
+
+/// Answers of the queries among the events of a graph with n vertices, in order.
+pub fn dynamic_connectivity(n: usize, events: &[Event]) -> Vec<bool> {
+    let queries: Vec<(usize, usize)> = events.iter()
+        .filter_map(|&e| match e { Event::Query(u, v) => Some((u, v)), _ => None })
+        .collect();
+    if queries.is_empty() {
+        return Vec::new();
+    }
+    let size = queries.len().next_power_of_two();
+    let mut edges = vec![Vec::new(); 2 * size];
+    // edge -> first query of each copy alive
+    let mut alive: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    let mut time = 0;
+    for &e in events {
+        match e {
+            Event::Add(u, v) => alive.entry((u.min(v), u.max(v))).or_default().push(time),
+            Event::Remove(u, v) => {
+                let edge = (u.min(v), u.max(v));
+                let start = alive.get_mut(&edge).and_then(|s| s.pop()).expect("removing a missing edge");
+                add_edge(&mut edges, size, start, time, edge);
+            },
+            Event::Query(..) => time += 1,
+        }
+    }
+    for (edge, starts) in alive {
+        for start in starts {
+            add_edge(&mut edges, size, start, time, edge);
+        }
+    }
+    let mut answers = vec![false; queries.len()];
+    let mut ds = RollbackDSet::new(n);
+    solve(1, size, &edges, &queries, &mut ds, &mut answers);
+    answers
+}
+
+/// Add the edge to the nodes covering the queries [l, r).
+fn add_edge(edges: &mut [Vec<(usize, usize)>], size: usize, l: usize, r: usize, edge: (usize, usize)) {
+    let (mut l, mut r) = (l + size, r + size);
+    while l < r {
+        if l & 1 == 1 {
+            edges[l].push(edge);
+            l += 1;
+        }
+        if r & 1 == 1 {
+            r -= 1;
+            edges[r].push(edge);
+        }
+        l >>= 1;
+        r >>= 1;
+    }
+}
+
+fn solve(
+    k: usize,
+    size: usize,
+    edges: &[Vec<(usize, usize)>],
+    queries: &[(usize, usize)],
+    ds: &mut RollbackDSet,
+    answers: &mut [bool],
+) {
+    if k >= size && k - size >= queries.len() {
+        return;
+    }
+    let snapshot = ds.snapshot();
+    for &(u, v) in &edges[k] {
+        ds.merge(u, v);
+    }
+    if k >= size {
+        let (u, v) = queries[k - size];
+        answers[k - size] = ds.same(u, v);
+    } else {
+        solve(2 * k, size, edges, queries, ds, answers);
+        solve(2 * k + 1, size, edges, queries, ds, answers);
+    }
+    ds.rollback(snapshot);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn test_dynamic_connectivity() {
+        use Event::*;
+        let events = [
+            Query(0, 1), Add(0, 1), Add(1, 2), Query(0, 2),
+            Remove(0, 1), Query(0, 2), Query(1, 2), Add(2, 0), Query(0, 1),
+        ];
+        assert_eq!(dynamic_connectivity(3, &events), vec![false, true, false, true, true]);
+        assert_eq!(dynamic_connectivity(3, &[Add(0, 1)]), vec![]);
+    }
+
+    #[test]
+    fn test_dynamic_connectivity_random() {
+        let mut rng = Rng(88172645463325252);
+        let n = 8;
+        let mut events = vec![];
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut expected = vec![];
+        for _ in 0..600 {
+            match rng.next(3) {
+                0 => {
+                    let e = (rng.next(n), rng.next(n));
+                    events.push(Event::Add(e.0, e.1));
+                    edges.push(e);
+                },
+                1 if !edges.is_empty() => {
+                    let (u, v) = edges.swap_remove(rng.next(edges.len()));
+                    events.push(Event::Remove(v, u));
+                },
+                _ => {
+                    let (u, v) = (rng.next(n), rng.next(n));
+                    events.push(Event::Query(u, v));
+                    // brute force: flood fill from u
+                    let mut seen = vec![false; n];
+                    let mut stack = vec![u];
+                    seen[u] = true;
+                    while let Some(x) = stack.pop() {
+                        for &(a, b) in &edges {
+                            for (a, b) in [(a, b), (b, a)] {
+                                if a == x && !seen[b] {
+                                    seen[b] = true;
+                                    stack.push(b);
+                                }
+                            }
+                        }
+                    }
+                    expected.push(seen[v]);
+                },
+            }
+        }
+        assert_eq!(dynamic_connectivity(n, &events), expected);
+    }
+}
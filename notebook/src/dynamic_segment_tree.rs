@@ -0,0 +1,162 @@
+// ----- Dynamic (sparse) Segment Tree -----
+// Same ranges as segment_tree::SegTree but with i64 positions, e.g. [-10^18, 10^18].
+// Nodes are created the first time an update reaches them, so memory is
+// O(updates * log(to - fr)). Positions never updated hold the identity.
+use {
+    std::ops::Range,
+    crate::segment_tree::Monoid,
+};
+
+/// Index 0 is the root, which is never a child, so 0 also means "no child".
+const NONE: usize = 0;
+
+struct Node<S> {
+    value: S,
+    left: usize,
+    right: usize,
+}
+
+pub struct DynSegTree<M: Monoid> {
+    fr: i64,
+    to: i64,
+    nodes: Vec<Node<M::S>>,
+}
+
+/// Middle of [fr, to] rounded down, without overflowing on huge ranges.
+fn mid(fr: i64, to: i64) -> i64 {
+    (fr >> 1) + (to >> 1) + (fr & to & 1)
+}
+
+impl<M: Monoid> DynSegTree<M> {
+    /// Tree over positions [fr, to], all of them set to the identity.
+    pub fn new(fr: i64, to: i64) -> DynSegTree<M> {
+        debug_assert!(fr <= to);
+        let root = Node { value: M::identity(), left: NONE, right: NONE };
+        DynSegTree { fr, to, nodes: vec![root] }
+    }
+
+    /// Combination of the values in [a, b].
+    pub fn query(&self, a: i64, b: i64) -> M::S {
+        debug_assert!(self.fr <= a && a <= b && b <= self.to);
+        self.query_rec(0, self.fr, self.to, a, b)
+    }
+
+    fn query_rec(&self, k: usize, fr: i64, to: i64, a: i64, b: i64) -> M::S {
+        if a == fr && b == to {
+            return self.nodes[k].value.clone();
+        }
+        let mid = mid(fr, to);
+        let node = &self.nodes[k];
+        let mut result = M::identity();
+        if a <= mid && node.left != NONE {
+            result = self.query_rec(node.left, fr, mid, a, b.min(mid));
+        }
+        if mid < b && node.right != NONE {
+            result = M::op(&result, &self.query_rec(node.right, mid + 1, to, a.max(mid + 1), b));
+        }
+        result
+    }
+
+    /// Combination of the values in [r.start, r.end), the identity if empty.
+    pub fn query_range(&self, r: Range<i64>) -> M::S {
+        if r.is_empty() { M::identity() } else { self.query(r.start, r.end - 1) }
+    }
+
+    pub fn get(&self, i: i64) -> M::S {
+        self.query(i, i)
+    }
+
+    pub fn update(&mut self, i: i64, v: M::S) {
+        debug_assert!(self.fr <= i && i <= self.to);
+        self.update_rec(0, self.fr, self.to, i, v);
+    }
+
+    fn update_rec(&mut self, k: usize, fr: i64, to: i64, i: i64, v: M::S) {
+        if fr == to {
+            self.nodes[k].value = v;
+            return;
+        }
+        let mid = mid(fr, to);
+        if i <= mid {
+            let left = self.child(k, true);
+            self.update_rec(left, fr, mid, i, v);
+        } else {
+            let right = self.child(k, false);
+            self.update_rec(right, mid + 1, to, i, v);
+        }
+        let Node { left, right, .. } = self.nodes[k];
+        self.nodes[k].value = M::op(&self.value(left), &self.value(right));
+    }
+
+    fn value(&self, k: usize) -> M::S {
+        if k == NONE { M::identity() } else { self.nodes[k].value.clone() }
+    }
+
+    /// Index of the left or right child of node k, created if missing.
+    fn child(&mut self, k: usize, left: bool) -> usize {
+        let existing = if left { self.nodes[k].left } else { self.nodes[k].right };
+        if existing != NONE {
+            return existing;
+        }
+        self.nodes.push(Node { value: M::identity(), left: NONE, right: NONE });
+        let created = self.nodes.len() - 1;
+        if left { self.nodes[k].left = created; } else { self.nodes[k].right = created; }
+        created
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use crate::segment_tree::{Max, Sum};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_dyn_seg_tree() {
+        let mut stree = DynSegTree::<Sum<i64>>::new(1, 1_000_000_000);
+        assert_eq!(0, stree.query(1, 1_000_000_000));
+        stree.update(5, 10);
+        stree.update(999_999_999, 4);
+        stree.update(5, 15);
+        assert_eq!(19, stree.query(1, 1_000_000_000));
+        assert_eq!(15, stree.query(2, 999_999_998));
+        assert_eq!(4, stree.get(999_999_999));
+        assert_eq!(0, stree.query_range(6..6));
+
+        let mut max = DynSegTree::<Max<i64>>::new(i64::MIN, i64::MAX);
+        max.update(i64::MIN, -3);
+        max.update(i64::MAX, -7);
+        max.update(0, -5);
+        assert_eq!(-3, max.query(i64::MIN, i64::MAX));
+        assert_eq!(-5, max.query(i64::MIN + 1, i64::MAX));
+        assert_eq!(-7, max.query(1, i64::MAX));
+    }
+
+    #[test]
+    fn test_dyn_seg_tree_random() {
+        let mut rng = Rng(88172645463325252);
+        let (fr, to) = (-1_000_000_000_000_000_000, 1_000_000_000_000_000_000);
+        let width = (to - fr) as usize + 1;
+        let mut stree = DynSegTree::<Sum<i64>>::new(fr, to);
+        let mut values = BTreeMap::new();
+        let mut positions = vec![];
+        // known positions half of the time, so that queries hit updated values
+        let pick = |rng: &mut Rng, positions: &[i64]| match rng.next(2) {
+            0 if !positions.is_empty() => positions[rng.next(positions.len())],
+            _ => fr + rng.next(width) as i64,
+        };
+        for _ in 0..500 {
+            let i = pick(&mut rng, &positions);
+            let v = rng.next(1000) as i64;
+            stree.update(i, v);
+            values.insert(i, v);
+            positions.push(i);
+
+            let (a, b) = (pick(&mut rng, &positions), pick(&mut rng, &positions));
+            let (a, b) = (a.min(b), a.max(b));
+            assert_eq!(stree.query(a, b), values.range(a..=b).map(|(_, v)| v).sum::<i64>());
+        }
+        assert!(stree.nodes.len() <= 1 + 500 * 61);
+    }
+}
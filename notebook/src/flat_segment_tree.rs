@@ -0,0 +1,182 @@
+// ----- Iterative (bottom-up) Segment Tree -----
+// Same API as segment_tree::SegTree, the nodes live in a single Vec: node 1 is
+// the root and the children of node k are 2k and 2k+1.
+use {
+    std::ops::Range,
+    crate::segment_tree::Monoid,
+};
+
+pub struct FlatSegTree<M: Monoid> {
+    fr: usize,
+    n: usize,
+    size: usize,
+    value: Vec<M::S>,
+}
+
+impl<M: Monoid> FlatSegTree<M> {
+    /// Tree over positions [fr, to], all of them set to initial_value.
+    pub fn new(fr: usize, to: usize, initial_value: M::S) -> FlatSegTree<M> {
+        debug_assert!(fr <= to);
+        FlatSegTree::build(fr, &vec![initial_value; to - fr + 1])
+    }
+
+    /// Tree over positions [0, values.len()).
+    pub fn from_slice(values: &[M::S]) -> FlatSegTree<M> {
+        debug_assert!(!values.is_empty());
+        FlatSegTree::build(0, values)
+    }
+
+    fn build(fr: usize, values: &[M::S]) -> FlatSegTree<M> {
+        let n = values.len();
+        let size = n.next_power_of_two();
+        let mut value = vec![M::identity(); 2 * size];
+        value[size..size + n].clone_from_slice(values);
+        for k in (1..size).rev() {
+            value[k] = M::op(&value[2 * k], &value[2 * k + 1]);
+        }
+        FlatSegTree { fr, n, size, value }
+    }
+
+    /// Combination of the values in [a, b].
+    pub fn query(&self, a: usize, b: usize) -> M::S {
+        debug_assert!(self.fr <= a && a <= b && b < self.fr + self.n);
+        let mut l = a - self.fr + self.size;
+        let mut r = b - self.fr + self.size + 1;
+        let mut left = M::identity();
+        let mut right = M::identity();
+        while l < r {
+            if l & 1 == 1 {
+                left = M::op(&left, &self.value[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = M::op(&self.value[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::op(&left, &right)
+    }
+
+    /// Combination of the values in [r.start, r.end), the identity if empty.
+    pub fn query_range(&self, r: Range<usize>) -> M::S {
+        if r.is_empty() { M::identity() } else { self.query(r.start, r.end - 1) }
+    }
+
+    pub fn update(&mut self, i: usize, v: M::S) {
+        debug_assert!(self.fr <= i && i < self.fr + self.n);
+        let mut k = i - self.fr + self.size;
+        self.value[k] = v;
+        while k > 1 {
+            k >>= 1;
+            self.value[k] = M::op(&self.value[2 * k], &self.value[2 * k + 1]);
+        }
+    }
+
+    /// Largest r such that f(op of [l, r)) holds, e.g. the first position
+    /// where a prefix sum exceeds k. f must hold for the identity and be
+    /// monotone: once it fails it keeps failing as r grows.
+    pub fn max_right(&self, l: usize, f: impl Fn(&M::S) -> bool) -> usize {
+        debug_assert!(self.fr <= l && l <= self.fr + self.n && f(&M::identity()));
+        if l == self.fr + self.n { return l; }
+        let mut k = l - self.fr + self.size;
+        let mut acc = M::identity();
+        loop {
+            k >>= k.trailing_zeros();
+            if !f(&M::op(&acc, &self.value[k])) {
+                while k < self.size {
+                    k *= 2;
+                    let value = M::op(&acc, &self.value[k]);
+                    if f(&value) {
+                        acc = value;
+                        k += 1;
+                    }
+                }
+                return k - self.size + self.fr;
+            }
+            acc = M::op(&acc, &self.value[k]);
+            k += 1;
+            if k.is_power_of_two() { return self.fr + self.n; }
+        }
+    }
+
+    /// Smallest l such that f(op of [l, r)) holds. f must hold for the
+    /// identity and be monotone: once it fails it keeps failing as l decreases.
+    pub fn min_left(&self, r: usize, f: impl Fn(&M::S) -> bool) -> usize {
+        debug_assert!(self.fr <= r && r <= self.fr + self.n && f(&M::identity()));
+        if r == self.fr { return r; }
+        let mut k = r - self.fr + self.size;
+        let mut acc = M::identity();
+        loop {
+            k -= 1;
+            while k > 1 && k % 2 == 1 { k >>= 1; }
+            if !f(&M::op(&self.value[k], &acc)) {
+                while k < self.size {
+                    k = 2 * k + 1;
+                    let value = M::op(&self.value[k], &acc);
+                    if f(&value) {
+                        acc = value;
+                        k -= 1;
+                    }
+                }
+                return k + 1 - self.size + self.fr;
+            }
+            acc = M::op(&self.value[k], &acc);
+            if k.is_power_of_two() { return self.fr; }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Concat;
+    use crate::segment_tree::{Max, Sum};
+
+    #[test]
+    fn test_flat_seg_tree() {
+        let mut stree = FlatSegTree::<Sum<i32>>::new(0, 9, 0);
+        assert_eq!(0, stree.query(0, 9));
+        stree.update(4, 15);
+        stree.update(5, 1);
+        stree.update(9, 4);
+        assert_eq!(20, stree.query(1, 9));
+        assert_eq!(16, stree.query(2, 8));
+        assert_eq!(0, stree.query_range(3..3));
+
+        let mut max = FlatSegTree::<Max<i64>>::new(5, 7, -1);
+        max.update(6, 3);
+        assert_eq!(-1, max.query(5, 5));
+        assert_eq!(3, max.query(5, 7));
+
+        let letters: Vec<String> = "abcdefg".chars().map(String::from).collect();
+        let mut concat = FlatSegTree::<Concat>::from_slice(&letters);
+        concat.update(3, String::from("D"));
+        for a in 0..letters.len() {
+            for b in a..=letters.len() {
+                assert_eq!(concat.query_range(a..b), "abcDefg"[a..b]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flat_seg_tree_binary_search() {
+        let v: Vec<u32> = vec![3, 0, 4, 1, 5, 9, 2];
+        let stree = FlatSegTree::<Sum<u32>>::from_slice(&v);
+        for k in 0..30 {
+            for l in 0..=v.len() {
+                let expected = (l..=v.len()).rev().find(|&r| v[l..r].iter().sum::<u32>() <= k).unwrap();
+                assert_eq!(stree.max_right(l, |&s| s <= k), expected);
+            }
+            for r in 0..=v.len() {
+                let expected = (0..=r).find(|&l| v[l..r].iter().sum::<u32>() <= k).unwrap();
+                assert_eq!(stree.min_left(r, |&s| s <= k), expected);
+            }
+        }
+        let stree = FlatSegTree::<Max<i64>>::new(3, 6, 1);
+        assert_eq!(stree.max_right(3, |&m| m < 1), 3);
+        assert_eq!(stree.max_right(5, |&m| m < 2), 7);
+        assert_eq!(stree.min_left(7, |&m| m < 2), 3);
+    }
+}
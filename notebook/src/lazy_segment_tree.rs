@@ -0,0 +1,409 @@
+// ----- Lazy Segment Tree -----
+// Range updates and range queries, flat and iterative like FlatSegTree.
+use {
+    std::{fmt::Debug, marker::PhantomData, ops::{Add, Mul, Range}},
+    crate::segment_tree::{Bounded, Max, Min, Monoid},
+};
+
+/// Updates F applied lazily to the values of the monoid M. Applying an update
+/// to a combination of values must give the combination of the updated values.
+pub trait Action {
+    type M: Monoid;
+    type F: Clone;
+    /// The update that leaves values unchanged.
+    fn id() -> Self::F;
+    /// Update that applies g first and then f.
+    fn compose(f: &Self::F, g: &Self::F) -> Self::F;
+    fn apply(f: &Self::F, x: &Value<Self>) -> Value<Self>;
+}
+
+pub type Value<A> = <<A as Action>::M as Monoid>::S;
+
+/// Sum along with the number of positions, (sum, len), needed to add a value
+/// to every position of a range.
+pub struct SumLen<T>(PhantomData<T>);
+
+impl<T: Copy + Default + Add<Output = T>> Monoid for SumLen<T> {
+    type S = (T, usize);
+    fn identity() -> (T, usize) { (T::default(), 0) }
+    fn op(a: &(T, usize), b: &(T, usize)) -> (T, usize) { (a.0 + b.0, a.1 + b.1) }
+}
+
+fn times<T>(x: T, len: usize) -> T
+where
+    T: Mul<Output = T> + TryFrom<i64>,
+    <T as TryFrom<i64>>::Error: Debug,
+{
+    x * T::try_from(len as i64).unwrap()
+}
+
+/// Range add, range sum. Values are (sum, len), e.g. (x, 1) for a single position.
+pub struct AddSum<T>(PhantomData<T>);
+/// Range add, range min. T::MAX is the identity of Min and stands for an empty
+/// range, so adds skip it: a position holding T::MAX never changes.
+pub struct AddMin<T>(PhantomData<T>);
+/// Range add, range max. Same as AddMin, a position holding T::MIN never changes.
+pub struct AddMax<T>(PhantomData<T>);
+/// Range assign, range sum. Values are (sum, len), e.g. (x, 1) for a single position.
+pub struct AssignSum<T>(PhantomData<T>);
+/// Range assign, range min.
+pub struct AssignMin<T>(PhantomData<T>);
+/// Range assign, range max.
+pub struct AssignMax<T>(PhantomData<T>);
+
+impl<T> Action for AddSum<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T> + TryFrom<i64>,
+    <T as TryFrom<i64>>::Error: Debug,
+{
+    type M = SumLen<T>;
+    type F = T;
+    fn id() -> T { T::default() }
+    fn compose(f: &T, g: &T) -> T { *f + *g }
+    fn apply(f: &T, x: &(T, usize)) -> (T, usize) { (x.0 + times(*f, x.1), x.1) }
+}
+
+impl<T: Copy + Default + Ord + Bounded + Add<Output = T>> Action for AddMin<T> {
+    type M = Min<T>;
+    type F = T;
+    fn id() -> T { T::default() }
+    fn compose(f: &T, g: &T) -> T { *f + *g }
+    // the identity stays as is, it stands for no values at all
+    fn apply(f: &T, x: &T) -> T { if *x == T::MAX { *x } else { *x + *f } }
+}
+
+impl<T: Copy + Default + Ord + Bounded + Add<Output = T>> Action for AddMax<T> {
+    type M = Max<T>;
+    type F = T;
+    fn id() -> T { T::default() }
+    fn compose(f: &T, g: &T) -> T { *f + *g }
+    fn apply(f: &T, x: &T) -> T { if *x == T::MIN { *x } else { *x + *f } }
+}
+
+impl<T> Action for AssignSum<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T> + TryFrom<i64>,
+    <T as TryFrom<i64>>::Error: Debug,
+{
+    type M = SumLen<T>;
+    type F = Option<T>;
+    fn id() -> Option<T> { None }
+    fn compose(f: &Option<T>, g: &Option<T>) -> Option<T> { f.or(*g) }
+    fn apply(f: &Option<T>, x: &(T, usize)) -> (T, usize) {
+        match f {
+            Some(v) => (times(*v, x.1), x.1),
+            None => *x,
+        }
+    }
+}
+
+impl<T: Copy + Ord + Bounded> Action for AssignMin<T> {
+    type M = Min<T>;
+    type F = Option<T>;
+    fn id() -> Option<T> { None }
+    fn compose(f: &Option<T>, g: &Option<T>) -> Option<T> { f.or(*g) }
+    fn apply(f: &Option<T>, x: &T) -> T { f.unwrap_or(*x) }
+}
+
+impl<T: Copy + Ord + Bounded> Action for AssignMax<T> {
+    type M = Max<T>;
+    type F = Option<T>;
+    fn id() -> Option<T> { None }
+    fn compose(f: &Option<T>, g: &Option<T>) -> Option<T> { f.or(*g) }
+    fn apply(f: &Option<T>, x: &T) -> T { f.unwrap_or(*x) }
+}
+
+pub struct LazySegTree<A: Action> {
+    fr: usize,
+    n: usize,
+    size: usize,
+    log: u32,
+    value: Vec<Value<A>>,
+    lazy: Vec<A::F>,
+}
+
+impl<A: Action> LazySegTree<A> {
+    /// Tree over positions [fr, to], all of them set to initial_value.
+    pub fn new(fr: usize, to: usize, initial_value: Value<A>) -> LazySegTree<A> {
+        debug_assert!(fr <= to);
+        LazySegTree::build(fr, &vec![initial_value; to - fr + 1])
+    }
+
+    /// Tree over positions [0, values.len()).
+    pub fn from_slice(values: &[Value<A>]) -> LazySegTree<A> {
+        debug_assert!(!values.is_empty());
+        LazySegTree::build(0, values)
+    }
+
+    fn build(fr: usize, values: &[Value<A>]) -> LazySegTree<A> {
+        let n = values.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros();
+        let mut value = vec![A::M::identity(); 2 * size];
+        value[size..size + n].clone_from_slice(values);
+        let lazy = vec![A::id(); size];
+        let mut stree = LazySegTree { fr, n, size, log, value, lazy };
+        for k in (1..size).rev() {
+            stree.pull(k);
+        }
+        stree
+    }
+
+    fn pull(&mut self, k: usize) {
+        self.value[k] = A::M::op(&self.value[2 * k], &self.value[2 * k + 1]);
+    }
+
+    fn apply_node(&mut self, k: usize, f: &A::F) {
+        self.value[k] = A::apply(f, &self.value[k]);
+        if k < self.size {
+            self.lazy[k] = A::compose(f, &self.lazy[k]);
+        }
+    }
+
+    fn push(&mut self, k: usize) {
+        let f = std::mem::replace(&mut self.lazy[k], A::id());
+        self.apply_node(2 * k, &f);
+        self.apply_node(2 * k + 1, &f);
+    }
+
+    /// Push the pending updates above the leaves l and r (exclusive).
+    fn push_borders(&mut self, l: usize, r: usize) {
+        for i in (1..=self.log).rev() {
+            if (l >> i) << i != l { self.push(l >> i); }
+            if (r >> i) << i != r { self.push((r - 1) >> i); }
+        }
+    }
+
+    /// Leaves of the positions [a, b], as a half-open range.
+    fn leaves(&self, a: usize, b: usize) -> (usize, usize) {
+        debug_assert!(self.fr <= a && a <= b && b < self.fr + self.n);
+        (a - self.fr + self.size, b - self.fr + self.size + 1)
+    }
+
+    /// Combination of the values in [a, b].
+    pub fn query(&mut self, a: usize, b: usize) -> Value<A> {
+        let (mut l, mut r) = self.leaves(a, b);
+        self.push_borders(l, r);
+        let mut left = A::M::identity();
+        let mut right = A::M::identity();
+        while l < r {
+            if l & 1 == 1 {
+                left = A::M::op(&left, &self.value[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = A::M::op(&self.value[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        A::M::op(&left, &right)
+    }
+
+    /// Combination of the values in [r.start, r.end), the identity if empty.
+    pub fn query_range(&mut self, r: Range<usize>) -> Value<A> {
+        if r.is_empty() { A::M::identity() } else { self.query(r.start, r.end - 1) }
+    }
+
+    /// Apply f to every value in [a, b].
+    pub fn apply(&mut self, a: usize, b: usize, f: A::F) {
+        let (l, r) = self.leaves(a, b);
+        self.push_borders(l, r);
+        let (mut l2, mut r2) = (l, r);
+        while l2 < r2 {
+            if l2 & 1 == 1 {
+                self.apply_node(l2, &f);
+                l2 += 1;
+            }
+            if r2 & 1 == 1 {
+                r2 -= 1;
+                self.apply_node(r2, &f);
+            }
+            l2 >>= 1;
+            r2 >>= 1;
+        }
+        for i in 1..=self.log {
+            if (l >> i) << i != l { self.pull(l >> i); }
+            if (r >> i) << i != r { self.pull((r - 1) >> i); }
+        }
+    }
+
+    /// Apply f to every value in [r.start, r.end).
+    pub fn apply_range(&mut self, r: Range<usize>, f: A::F) {
+        if !r.is_empty() { self.apply(r.start, r.end - 1, f); }
+    }
+
+    pub fn get(&mut self, i: usize) -> Value<A> {
+        let (k, _) = self.leaves(i, i);
+        for j in (1..=self.log).rev() {
+            self.push(k >> j);
+        }
+        self.value[k].clone()
+    }
+
+    pub fn update(&mut self, i: usize, v: Value<A>) {
+        let (k, _) = self.leaves(i, i);
+        for j in (1..=self.log).rev() {
+            self.push(k >> j);
+        }
+        self.value[k] = v;
+        for j in 1..=self.log {
+            self.pull(k >> j);
+        }
+    }
+
+    /// Largest r such that f(op of [l, r)) holds, e.g. the first position
+    /// where a prefix sum exceeds k. f must hold for the identity and be
+    /// monotone: once it fails it keeps failing as r grows.
+    pub fn max_right(&mut self, l: usize, f: impl Fn(&Value<A>) -> bool) -> usize {
+        debug_assert!(self.fr <= l && l <= self.fr + self.n && f(&A::M::identity()));
+        if l == self.fr + self.n { return l; }
+        let mut k = l - self.fr + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(k >> i);
+        }
+        let mut acc = A::M::identity();
+        loop {
+            k >>= k.trailing_zeros();
+            if !f(&A::M::op(&acc, &self.value[k])) {
+                while k < self.size {
+                    self.push(k);
+                    k *= 2;
+                    let value = A::M::op(&acc, &self.value[k]);
+                    if f(&value) {
+                        acc = value;
+                        k += 1;
+                    }
+                }
+                return k - self.size + self.fr;
+            }
+            acc = A::M::op(&acc, &self.value[k]);
+            k += 1;
+            if k.is_power_of_two() { return self.fr + self.n; }
+        }
+    }
+
+    /// Smallest l such that f(op of [l, r)) holds. f must hold for the
+    /// identity and be monotone: once it fails it keeps failing as l decreases.
+    pub fn min_left(&mut self, r: usize, f: impl Fn(&Value<A>) -> bool) -> usize {
+        debug_assert!(self.fr <= r && r <= self.fr + self.n && f(&A::M::identity()));
+        if r == self.fr { return r; }
+        let mut k = r - self.fr + self.size;
+        for i in (1..=self.log).rev() {
+            self.push((k - 1) >> i);
+        }
+        let mut acc = A::M::identity();
+        loop {
+            k -= 1;
+            while k > 1 && k % 2 == 1 { k >>= 1; }
+            if !f(&A::M::op(&self.value[k], &acc)) {
+                while k < self.size {
+                    self.push(k);
+                    k = 2 * k + 1;
+                    let value = A::M::op(&self.value[k], &acc);
+                    if f(&value) {
+                        acc = value;
+                        k -= 1;
+                    }
+                }
+                return k + 1 - self.size + self.fr;
+            }
+            acc = A::M::op(&self.value[k], &acc);
+            if k.is_power_of_two() { return self.fr; }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn test_lazy_seg_tree() {
+        let mut stree = LazySegTree::<AddSum<i64>>::new(0, 9, (0, 1));
+        stree.apply(2, 5, 3);
+        stree.apply(4, 9, -1);
+        assert_eq!(stree.query(0, 9), (12 - 6, 10));
+        assert_eq!(stree.query(4, 4).0, 2);
+        assert_eq!(stree.query_range(0..3).0, 3);
+        stree.update(5, (10, 1));
+        assert_eq!(stree.get(5).0, 10);
+        assert_eq!(stree.query(4, 6).0, 2 + 10 - 1);
+
+        let mut stree = LazySegTree::<AssignMin<i32>>::from_slice(&[5, 3, 8, 1]);
+        stree.apply(1, 3, Some(7));
+        assert_eq!(stree.query(0, 3), 5);
+        assert_eq!(stree.query(1, 3), 7);
+    }
+
+    fn brute_force_check<A: Action>(
+        rng: &mut Rng,
+        random_value: impl Fn(&mut Rng) -> Value<A>,
+        random_update: impl Fn(&mut Rng) -> A::F,
+    ) where Value<A>: PartialEq + Debug {
+        let n = 1 + rng.next(40);
+        let mut values: Vec<Value<A>> = (0..n).map(|_| random_value(rng)).collect();
+        let mut stree = LazySegTree::<A>::from_slice(&values);
+        for _ in 0..300 {
+            let a = rng.next(n);
+            let b = a + rng.next(n - a);
+            match rng.next(3) {
+                0 => {
+                    let f = random_update(rng);
+                    stree.apply(a, b, f.clone());
+                    for x in &mut values[a..=b] { *x = A::apply(&f, x); }
+                },
+                1 => {
+                    let v = random_value(rng);
+                    stree.update(a, v.clone());
+                    values[a] = v;
+                },
+                _ => {
+                    let expected = values[a..=b].iter().fold(A::M::identity(), |acc, x| A::M::op(&acc, x));
+                    assert_eq!(stree.query(a, b), expected);
+                    assert_eq!(stree.get(b), values[b]);
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_seg_tree_binary_search() {
+        let mut rng = Rng(2463534242);
+        let n = 37;
+        let mut values = vec![0; n];
+        let mut stree = LazySegTree::<AddSum<i64>>::new(0, n - 1, (0, 1));
+        for _ in 0..200 {
+            let a = rng.next(n);
+            let b = a + rng.next(n - a);
+            let x = rng.next(10) as i64;
+            stree.apply(a, b, x);
+            for v in &mut values[a..=b] { *v += x; }
+            let k = rng.next(300) as i64;
+            let l = rng.next(n + 1);
+            let r = rng.next(n + 1);
+            let expected = (l..=n).rev().find(|&r| values[l..r].iter().sum::<i64>() <= k).unwrap();
+            assert_eq!(stree.max_right(l, |s| s.0 <= k), expected);
+            let expected = (0..=r).find(|&l| values[l..r].iter().sum::<i64>() <= k).unwrap();
+            assert_eq!(stree.min_left(r, |s| s.0 <= k), expected);
+        }
+    }
+
+    #[test]
+    fn test_lazy_seg_tree_brute_force() {
+        let mut rng = Rng(88172645463325252);
+        let value = |rng: &mut Rng| rng.next(200) as i64 - 100;
+        let update = |rng: &mut Rng| rng.next(200) as i64 - 100;
+        let assign = |rng: &mut Rng| if rng.next(4) == 0 { None } else { Some(rng.next(200) as i64 - 100) };
+        for _ in 0..20 {
+            brute_force_check::<AddSum<i64>>(&mut rng, |rng| (value(rng), 1), update);
+            brute_force_check::<AddMin<i64>>(&mut rng, value, update);
+            brute_force_check::<AddMax<i64>>(&mut rng, value, update);
+            brute_force_check::<AssignSum<i64>>(&mut rng, |rng| (value(rng), 1), assign);
+            brute_force_check::<AssignMin<i64>>(&mut rng, value, assign);
+            brute_force_check::<AssignMax<i64>>(&mut rng, value, assign);
+        }
+    }
+}
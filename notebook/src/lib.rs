@@ -1,5 +1,26 @@
+pub mod combinatorics;
+pub mod crt;
+pub mod debug;
 pub mod disjoint_set;
+pub mod dyn_zn;
+pub mod dynamic_connectivity;
+pub mod dynamic_segment_tree;
+pub mod fenwick;
+pub mod flat_segment_tree;
+pub mod lazy_segment_tree;
 pub mod matrix_adj_iter;
+pub mod mersenne61;
+pub mod montgomery;
+pub mod ntt;
+pub mod number_theory;
+pub mod persistent_segment_tree;
+pub mod poly;
+pub mod scanner;
 pub mod segment_tree;
 pub mod sieve;
+pub mod sparse_table;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod writer;
 pub mod zn;
+pub mod zn64;
@@ -0,0 +1,159 @@
+// ----- Integers modulo the Mersenne prime 2^61 - 1 -----
+// For polynomial hashing: with a random base, two different strings of length
+// n collide with probability about n / 2^61. Reducing modulo 2^61 - 1 only
+// needs shifts and masks since 2^61 = 1.
+use {
+    std::ops::Range,
+    crate::zn::{modint_impls, ModInt},
+};
+
+const P: u64 = (1 << 61) - 1;
+
+/// Integers modulo 2^61 - 1, the value is always in [0, 2^61 - 1).
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Mersenne61(u64);
+
+impl Mersenne61 {
+    /// x mod P, for x < 2^122.
+    fn reduce(x: u128) -> u64 {
+        let x = (x >> 61) as u64 + (x as u64 & P);
+        let x = (x >> 61) + (x & P);
+        if x >= P { x - P } else { x }
+    }
+
+    pub fn new(x: impl Into<i64>) -> Mersenne61 {
+        Mersenne61::from(x.into())
+    }
+
+    /// Residue in [0, 2^61 - 1).
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// By Fermat's little theorem, P is prime. Panics for 0.
+    pub fn inverse(self) -> Mersenne61 {
+        assert!(self.0 != 0, "not invertible");
+        self.pow(P - 2)
+    }
+
+    /// self^e by repeated squaring, O(log e).
+    pub fn pow(self, mut e: u64) -> Mersenne61 {
+        let mut base = self;
+        let mut result = Mersenne61(1);
+        while e > 0 {
+            if e & 1 == 1 { result *= base; }
+            base *= base;
+            e >>= 1;
+        }
+        result
+    }
+}
+
+impl ModInt for Mersenne61 {
+    fn modulus() -> u64 { P }
+    fn value(self) -> u64 { self.0 }
+    fn pow(self, e: u64) -> Mersenne61 { Mersenne61::pow(self, e) }
+    fn inverse(self) -> Mersenne61 { Mersenne61::inverse(self) }
+}
+
+impl From<i64> for Mersenne61 {
+    fn from(x: i64) -> Mersenne61 { Mersenne61(x.rem_euclid(P as i64) as u64) }
+}
+
+impl std::ops::Add<Mersenne61> for Mersenne61 {
+    type Output = Mersenne61;
+    fn add(self, other: Mersenne61) -> Mersenne61 {
+        let x = self.0 + other.0;
+        Mersenne61(if x >= P { x - P } else { x })
+    }
+}
+
+impl std::ops::Sub<Mersenne61> for Mersenne61 {
+    type Output = Mersenne61;
+    fn sub(self, other: Mersenne61) -> Mersenne61 {
+        Mersenne61(if self.0 >= other.0 { self.0 - other.0 } else { self.0 + P - other.0 })
+    }
+}
+
+impl std::ops::Mul<Mersenne61> for Mersenne61 {
+    type Output = Mersenne61;
+    fn mul(self, other: Mersenne61) -> Mersenne61 {
+        Mersenne61(Mersenne61::reduce(self.0 as u128 * other.0 as u128))
+    }
+}
+
+impl std::ops::Neg for Mersenne61 {
+    type Output = Mersenne61;
+    fn neg(self) -> Mersenne61 { Mersenne61(0) - self }
+}
+
+modint_impls!([] Mersenne61, P);
+
+/// Polynomial hashes of the substrings of s in O(1) after O(n) precomputation:
+/// hash(s[a..b]) = s[a] * base^(b-a-1) + ... + s[b-1]. Pick the base at random,
+/// e.g. from the clock, so that tests can't be built against it.
+pub struct StringHash {
+    prefix: Vec<Mersenne61>,
+    power: Vec<Mersenne61>,
+}
+
+impl StringHash {
+    pub fn new<T: Copy + Into<u64>>(s: &[T], base: Mersenne61) -> StringHash {
+        let mut prefix = vec![Mersenne61(0); s.len() + 1];
+        let mut power = vec![Mersenne61(1); s.len() + 1];
+        for (i, &c) in s.iter().enumerate() {
+            prefix[i + 1] = prefix[i] * base + Mersenne61::from(c.into());
+            power[i + 1] = power[i] * base;
+        }
+        StringHash { prefix, power }
+    }
+
+    /// Hash of s[r.start..r.end].
+    pub fn get(&self, r: Range<usize>) -> Mersenne61 {
+        self.prefix[r.end] - self.prefix[r.start] * self.power[r.end - r.start]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use crate::zn64::Zn64;
+
+    #[test]
+    fn test_mersenne61() {
+        type Z = Zn64<P>;
+        let mut rng = Rng(88172645463325252);
+        for _ in 0..1000 {
+            let (a, b) = (rng.next_u64() as i64, rng.next_u64() as i64);
+            let (x, y) = (Mersenne61::new(a), Mersenne61::new(b));
+            let (zx, zy) = (Z::new(a), Z::new(b));
+            assert_eq!(x.value(), zx.value());
+            assert_eq!((x + y).value(), (zx + zy).value());
+            assert_eq!((x - y).value(), (zx - zy).value());
+            assert_eq!((x * y).value(), (zx * zy).value());
+            assert_eq!((x / y).value(), (zx / zy).value());
+            assert_eq!((-x).value(), (-zx).value());
+            assert_eq!(x.pow(b as u64).value(), zx.pow(b as u64).value());
+        }
+        let big = Mersenne61::new(-1);
+        assert_eq!(big * big, Mersenne61::new(1));
+        assert_eq!(format!("{} {:?}", big, [big + 3]), "2305843009213693950 [2]");
+        assert_eq!("2305843009213693953".parse::<Mersenne61>(), Ok(Mersenne61::new(2)));
+    }
+
+    #[test]
+    fn test_string_hash() {
+        let s = b"abracadabra";
+        let hash = StringHash::new(s, Mersenne61::new(1_000_003));
+        for a in 0..s.len() {
+            for b in a..=s.len() {
+                for c in 0..s.len() {
+                    for d in c..=s.len() {
+                        assert_eq!(hash.get(a..b) == hash.get(c..d), s[a..b] == s[c..d]);
+                    }
+                }
+            }
+        }
+    }
+}
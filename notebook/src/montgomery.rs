@@ -0,0 +1,151 @@
+// ----- Integers modulo an odd M in Montgomery form -----
+// Same API as zn::Zn, but x is stored as x * 2^32 mod M so that a product only
+// needs multiplications and shifts instead of a 64-bit %. Conversions happen
+// in new/from and value, so the representation never leaks. M odd and < 2^31.
+use crate::zn::{mod_inverse, modint_impls, ModInt};
+
+/// Integers modulo M, stored as x * 2^32 mod M in [0, M).
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MontZn<const M: u32>(u32);
+
+impl<const M: u32> MontZn<M> {
+    /// -1/M mod 2^32, by Newton's iteration: each step doubles the correct bits
+    /// and M * M = 1 mod 8 gives the first 3.
+    const NEG_INV: u32 = {
+        let mut inv = M;
+        let mut i = 0;
+        while i < 4 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub(M.wrapping_mul(inv)));
+            i += 1;
+        }
+        inv.wrapping_neg()
+    };
+    /// Fails the build for a bad M, wherever from_residue is used.
+    const CHECK: () = assert!(M % 2 == 1 && M < 1 << 31, "M must be odd and below 2^31");
+    /// 2^64 mod M, turns x into x * 2^32 through reduce.
+    const R2: u32 = ((1u128 << 64) % M as u128) as u32;
+
+    /// t / 2^32 mod M, for t < M * 2^32.
+    fn reduce(t: u64) -> u32 {
+        let m = (t as u32).wrapping_mul(Self::NEG_INV);
+        let u = ((t + m as u64 * M as u64) >> 32) as u32;
+        if u >= M { u - M } else { u }
+    }
+
+    /// From a residue in [0, M).
+    fn from_residue(x: u32) -> MontZn<M> {
+        let () = Self::CHECK;
+        MontZn(Self::reduce(x as u64 * Self::R2 as u64))
+    }
+
+    pub fn new(x: impl Into<i64>) -> MontZn<M> {
+        MontZn::from(x.into())
+    }
+
+    /// Residue in [0, M).
+    pub fn value(self) -> u32 {
+        Self::reduce(self.0 as u64)
+    }
+
+    /// Panics if gcd(self, M) != 1.
+    pub fn inverse(self) -> MontZn<M> {
+        MontZn::new(mod_inverse(self.value() as i64, M as i64).expect("not invertible"))
+    }
+
+    /// self^e by repeated squaring, O(log e).
+    pub fn pow(self, mut e: u64) -> MontZn<M> {
+        let mut base = self;
+        let mut result = MontZn::from_residue(1 % M);
+        while e > 0 {
+            if e & 1 == 1 { result *= base; }
+            base *= base;
+            e >>= 1;
+        }
+        result
+    }
+}
+
+impl<const M: u32> ModInt for MontZn<M> {
+    fn modulus() -> u64 { M as u64 }
+    fn value(self) -> u64 { MontZn::value(self) as u64 }
+    fn pow(self, e: u64) -> MontZn<M> { MontZn::pow(self, e) }
+    fn inverse(self) -> MontZn<M> { MontZn::inverse(self) }
+}
+
+impl<const M: u32> From<i64> for MontZn<M> {
+    fn from(x: i64) -> MontZn<M> { MontZn::from_residue(x.rem_euclid(M as i64) as u32) }
+}
+
+impl<const M: u32> std::ops::Add<MontZn<M>> for MontZn<M> {
+    type Output = MontZn<M>;
+    fn add(self, other: MontZn<M>) -> MontZn<M> {
+        let x = self.0 + other.0;
+        MontZn(if x >= M { x - M } else { x })
+    }
+}
+
+impl<const M: u32> std::ops::Sub<MontZn<M>> for MontZn<M> {
+    type Output = MontZn<M>;
+    fn sub(self, other: MontZn<M>) -> MontZn<M> {
+        MontZn(if self.0 >= other.0 { self.0 - other.0 } else { self.0 + M - other.0 })
+    }
+}
+
+impl<const M: u32> std::ops::Mul<MontZn<M>> for MontZn<M> {
+    type Output = MontZn<M>;
+    fn mul(self, other: MontZn<M>) -> MontZn<M> { MontZn(Self::reduce(self.0 as u64 * other.0 as u64)) }
+}
+
+impl<const M: u32> std::ops::Neg for MontZn<M> {
+    type Output = MontZn<M>;
+    fn neg(self) -> MontZn<M> { MontZn(0) - self }
+}
+
+modint_impls!([const M: u32] MontZn<M>, M);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use crate::zn::Zn;
+
+    fn check_against_zn<const M: u32>(rng: &mut Rng) {
+        for _ in 0..1000 {
+            let (a, b) = (rng.next_u64() as i64, rng.next_u64() as i64);
+            let (x, y) = (MontZn::<M>::new(a), MontZn::<M>::new(b));
+            let (zx, zy) = (Zn::<M>::new(a), Zn::<M>::new(b));
+            assert_eq!(x.value(), zx.value());
+            assert_eq!((x + y).value(), (zx + zy).value());
+            assert_eq!((x - y).value(), (zx - zy).value());
+            assert_eq!((x * y).value(), (zx * zy).value());
+            assert_eq!((-x).value(), (-zx).value());
+            assert_eq!(x.pow(b as u64).value(), zx.pow(b as u64).value());
+            if zy.value() != 0 && (M != 9 || zy.value() % 3 != 0) {
+                assert_eq!((x / y).value(), (zx / zy).value());
+            }
+        }
+    }
+
+    #[test]
+    fn test_mont_zn() {
+        let mut rng = Rng(88172645463325252);
+        check_against_zn::<998244353>(&mut rng);
+        check_against_zn::<1000000007>(&mut rng);
+        check_against_zn::<9>(&mut rng);
+        check_against_zn::<1>(&mut rng);
+        check_against_zn::<2147483647>(&mut rng);
+
+        type Z = MontZn<998244353>;
+        let mut x = Z::new(10);
+        x += 5;
+        x *= Z::new(3);
+        x -= 50u32;
+        x /= 5;
+        assert_eq!(x, Z::new(-1));
+        assert_eq!(format!("{} {:?}", x, [Z::new(7)]), "998244352 [7]");
+        assert_eq!("-1".parse::<Z>(), Ok(x));
+        let v: Vec<Z> = (1..=5).map(Z::new).collect();
+        assert_eq!(v.iter().sum::<Z>(), Z::new(15));
+        assert_eq!(v.iter().product::<Z>(), Z::new(120));
+    }
+}
@@ -0,0 +1,230 @@
+// ----- Number Theoretic Transform -----
+// Convolution (polynomial product) of sequences of Zn<M> in O((n + m) log(n + m)).
+// The FFT runs over Zn<M> itself when M = c * 2^k + 1 with 2^k >= n + m - 1,
+// e.g. 998244353 = 119 * 2^23 + 1. Any other M, e.g. 10^9 + 7, goes through
+// three such primes and Garner's algorithm to rebuild the exact coefficients.
+use crate::zn::Zn;
+
+/// Below this length the O(n m) product is faster.
+const NAIVE_LIMIT: usize = 60;
+
+/// Smallest primitive root modulo the prime m, at compile time. 0 if m isn't prime.
+/// A const copy of number_theory::primitive_root, which needs a sieve and can't
+/// give Ntt::<M>::ROOT. In u64 so that p * p doesn't overflow for m near 2^32.
+#[allow(clippy::manual_is_multiple_of)]
+const fn primitive_root(m: u32) -> u32 {
+    if !is_prime(m) { return 0; }
+    if m == 2 { return 1; }
+    let mut factors = [0u64; 32];
+    let mut count = 0;
+    let mut x = m as u64 - 1;
+    let mut p = 2;
+    while p * p <= x {
+        if x % p == 0 {
+            factors[count] = p;
+            count += 1;
+            while x % p == 0 { x /= p; }
+        }
+        p += 1;
+    }
+    if x > 1 {
+        factors[count] = x;
+        count += 1;
+    }
+    let mut g = 2;
+    while g < m {
+        let mut i = 0;
+        while i < count && pow_mod(g, (m as u64 - 1) / factors[i], m) != 1 {
+            i += 1;
+        }
+        if i == count { return g; }
+        g += 1;
+    }
+    0
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+const fn is_prime(m: u32) -> bool {
+    let m = m as u64;
+    let mut p = 2;
+    while p * p <= m {
+        if m % p == 0 { return false; }
+        p += 1;
+    }
+    m >= 2
+}
+
+const fn pow_mod(mut base: u32, mut e: u64, m: u32) -> u32 {
+    let mut result = 1u64;
+    while e > 0 {
+        if e & 1 == 1 { result = result * base as u64 % m as u64; }
+        base = (base as u64 * base as u64 % m as u64) as u32;
+        e >>= 1;
+    }
+    result as u32
+}
+
+struct Ntt<const M: u32>;
+
+impl<const M: u32> Ntt<M> {
+    const ROOT: u32 = primitive_root(M);
+    const FRIENDLY: bool = Self::ROOT != 0;
+    /// Largest power of two that divides M - 1, the longest possible transform.
+    const MAX_LEN: usize = 1 << (M - 1).trailing_zeros();
+
+    /// In place transform, a.len() must be a power of two <= MAX_LEN. The
+    /// inverse includes the division by a.len().
+    fn transform(a: &mut [Zn<M>], inverse: bool) {
+        let n = a.len();
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j ^= bit;
+            if i < j { a.swap(i, j); }
+        }
+        let mut len = 2;
+        while len <= n {
+            let mut w = Zn::<M>::new(Self::ROOT).pow((M as u64 - 1) / len as u64);
+            if inverse { w = w.inverse(); }
+            for chunk in a.chunks_mut(len) {
+                let (lo, hi) = chunk.split_at_mut(len / 2);
+                let mut wi = Zn::new(1);
+                for (x, y) in lo.iter_mut().zip(hi) {
+                    let (u, v) = (*x, *y * wi);
+                    *x = u + v;
+                    *y = u - v;
+                    wi *= w;
+                }
+            }
+            len <<= 1;
+        }
+        if inverse {
+            let inv_n = Zn::<M>::new(n as i64).inverse();
+            for x in a.iter_mut() { *x *= inv_n; }
+        }
+    }
+
+    fn convolution(a: &[Zn<M>], b: &[Zn<M>]) -> Vec<Zn<M>> {
+        let len = a.len() + b.len() - 1;
+        let size = len.next_power_of_two();
+        assert!(size <= Self::MAX_LEN, "convolution of length {} over the limit {} of {}", len, Self::MAX_LEN, M);
+        let mut fa = a.to_vec();
+        let mut fb = b.to_vec();
+        fa.resize(size, Zn::new(0));
+        fb.resize(size, Zn::new(0));
+        Self::transform(&mut fa, false);
+        Self::transform(&mut fb, false);
+        for (x, y) in fa.iter_mut().zip(&fb) { *x *= *y; }
+        Self::transform(&mut fa, true);
+        fa.truncate(len);
+        fa
+    }
+}
+
+fn naive<const M: u32>(a: &[Zn<M>], b: &[Zn<M>]) -> Vec<Zn<M>> {
+    let mut c = vec![Zn::new(0); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            c[i + j] += x * y;
+        }
+    }
+    c
+}
+
+/// Product modulo the NTT friendly primes M1, M2 and M3, then each coefficient
+/// x = r1 + t1 m1 + t2 m1 m2 with t1, t2 chosen to match r2 and r3. The exact
+/// coefficients are below n M^2 with n = min(a.len(), b.len()), so this needs
+/// n < m1 m2 m3 / M^2 ~ 2^86 / M^2, e.g. n < 2^21 for M near 2^32, and
+/// a.len() + b.len() <= 2^24 for m1. Written out instead of calling
+/// crt::garner, which allocates for every coefficient.
+fn convolution_garner<const M: u32>(a: &[Zn<M>], b: &[Zn<M>]) -> Vec<Zn<M>> {
+    const M1: u32 = 754974721;
+    const M2: u32 = 167772161;
+    const M3: u32 = 469762049;
+    let n = a.len().min(b.len()) as u128;
+    let max = (M as u128 - 1) * (M as u128 - 1);
+    assert!(n * max < M1 as u128 * M2 as u128 * M3 as u128, "coefficients too large for the three primes");
+    fn convert<const P: u32, const M: u32>(a: &[Zn<M>]) -> Vec<Zn<P>> {
+        a.iter().map(|x| Zn::new(x.value())).collect()
+    }
+    let c1 = Ntt::<M1>::convolution(&convert(a), &convert(b));
+    let c2 = Ntt::<M2>::convolution(&convert(a), &convert(b));
+    let c3 = Ntt::<M3>::convolution(&convert(a), &convert(b));
+    let inv_m1_mod_m2 = Zn::<M2>::new(M1).inverse();
+    let inv_m1m2_mod_m3 = (Zn::<M3>::new(M1) * Zn::new(M2)).inverse();
+    let m1m2_mod_m = Zn::<M>::new(M1) * Zn::new(M2);
+    (0..c1.len())
+        .map(|i| {
+            let r1 = c1[i].value();
+            let t1 = (c2[i] - Zn::new(r1)) * inv_m1_mod_m2;
+            let x12 = r1 as i64 + M1 as i64 * t1.value() as i64;
+            let t2 = (c3[i] - Zn::new(x12)) * inv_m1m2_mod_m3;
+            Zn::<M>::new(x12) + m1m2_mod_m * Zn::new(t2.value())
+        })
+        .collect()
+}
+
+/// c[k] = sum of a[i] * b[j] with i + j = k, empty if a or b is.
+pub fn convolution<const M: u32>(a: &[Zn<M>], b: &[Zn<M>]) -> Vec<Zn<M>> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    if a.len().min(b.len()) <= NAIVE_LIMIT {
+        return naive(a, b);
+    }
+    let len = a.len() + b.len() - 1;
+    if Ntt::<M>::FRIENDLY && len <= Ntt::<M>::MAX_LEN {
+        Ntt::<M>::convolution(a, b)
+    } else {
+        convolution_garner(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    fn random<const M: u32>(rng: &mut Rng, n: usize) -> Vec<Zn<M>> {
+        (0..n).map(|_| Zn::new(rng.next(M as usize) as i64)).collect()
+    }
+
+    fn check<const M: u32>(rng: &mut Rng) {
+        for (n, m) in [(1, 1), (3, 200), (61, 61), (100, 70), (257, 300), (1000, 999)] {
+            let a = random::<M>(rng, n);
+            let b = random::<M>(rng, m);
+            assert_eq!(convolution(&a, &b), naive(&a, &b));
+        }
+    }
+
+    #[test]
+    fn test_primitive_root() {
+        assert_eq!(primitive_root(998244353), 3);
+        assert_eq!(primitive_root(754974721), 11);
+        assert_eq!(primitive_root(167772161), 3);
+        assert_eq!(primitive_root(469762049), 3);
+        assert_eq!(primitive_root(7), 3);
+        assert_eq!(primitive_root(4294967291), 2);
+        assert_eq!(primitive_root(4294967295), 0);
+        assert_eq!(Ntt::<998244353>::MAX_LEN, 1 << 23);
+    }
+
+    #[test]
+    fn test_convolution() {
+        let mut rng = Rng(88172645463325252);
+        check::<998244353>(&mut rng);
+        check::<1000000007>(&mut rng);
+        check::<1000000>(&mut rng);
+        check::<2>(&mut rng);
+        check::<4294967291>(&mut rng);
+        type Z = Zn<998244353>;
+        let a: Vec<Z> = [1, 2, 3].map(Z::new).to_vec();
+        let b: Vec<Z> = [4, 5].map(Z::new).to_vec();
+        assert_eq!(convolution(&a, &b), [4, 13, 22, 15].map(Z::new));
+        assert_eq!(convolution(&a, &[]), []);
+    }
+}
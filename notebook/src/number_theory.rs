@@ -0,0 +1,167 @@
+// ----- Discrete logarithm, modular square root and primitive roots -----
+// For a modulus only known at runtime, so on plain u64 with products through
+// u128. Any modulus m >= 1 fits.
+use {
+    std::collections::HashMap,
+    crate::sieve::GenericSieve,
+};
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut e: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while e > 0 {
+        if e & 1 == 1 { result = mul_mod(result, base, m); }
+        base = mul_mod(base, base, m);
+        e >>= 1;
+    }
+    result
+}
+
+/// Smallest x >= 0 with a^x = b mod m, None if there is none. Baby-step
+/// giant-step in O(sqrt(m)) expected, with 0^0 = 1. m >= 1.
+pub fn discrete_log(a: u64, b: u64, mut m: u64) -> Option<u64> {
+    let (a, mut b) = (a % m, b % m);
+    // While gcd(a, m) = g > 1, a^x = b becomes (a/g) a^(x-1) = b/g mod m/g.
+    // k a^y = b with k = product of the a/g, and x = y + steps.
+    let mut k = 1 % m;
+    let mut steps = 0;
+    loop {
+        let g = gcd(a, m);
+        if g == 1 { break; }
+        if b == k { return Some(steps); }
+        if b % g != 0 { return None; }
+        b /= g;
+        m /= g;
+        k = (k as u128 * a as u128 / g as u128 % m as u128) as u64;
+        steps += 1;
+    }
+    // Now a is invertible, with x = n i - j: k (a^n)^i = b a^j
+    let n = (m as f64).sqrt() as u64 + 1;
+    let mut baby = HashMap::new();
+    let mut cur = b;
+    for j in 0..=n {
+        baby.insert(cur, j);
+        cur = mul_mod(cur, a, m);
+    }
+    let an = pow_mod(a, n, m);
+    let mut cur = k;
+    for i in 1..=n {
+        cur = mul_mod(cur, an, m);
+        if let Some(&j) = baby.get(&cur) {
+            return Some(n * i - j + steps);
+        }
+    }
+    None
+}
+
+/// The smaller x with x^2 = a mod p, None if a isn't a square. Tonelli-Shanks
+/// in O(log^2 p), p prime.
+pub fn sqrt_mod(a: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if a == 0 || p == 2 { return Some(a); }
+    if pow_mod(a, (p - 1) / 2, p) != 1 { return None; }
+    // p - 1 = q 2^s with q odd, z any non square
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let mut z = 2;
+    while pow_mod(z, (p - 1) / 2, p) == 1 { z += 1; }
+    let (mut m, mut c, mut t, mut x) = (s, pow_mod(z, q, p), pow_mod(a, q, p), pow_mod(a, q.div_ceil(2), p));
+    while t != 1 {
+        // t has order 2^i
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mul_mod(t2, t2, p);
+            i += 1;
+        }
+        let b = pow_mod(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mul_mod(b, b, p);
+        t = mul_mod(t, c, p);
+        x = mul_mod(x, b, p);
+    }
+    Some(x.min(p - x))
+}
+
+/// Smallest primitive root modulo the prime p: g is one iff g^((p-1)/q) != 1
+/// for every prime q dividing p - 1. The sieve must have N^2 > p to factor p - 1.
+pub fn primitive_root<const N: usize>(p: u64, sieve: &GenericSieve<u64, N>) -> u64 {
+    if p == 2 { return 1; }
+    let mut factors = sieve.factorize(p - 1);
+    factors.dedup();
+    (2..p).find(|&g| factors.iter().all(|&q| pow_mod(g, (p - 1) / q, p) != 1)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discrete_log() {
+        for m in 1..60 {
+            for a in 0..m {
+                for b in 0..m {
+                    // a^x mod m cycles within the first 2m powers
+                    let mut cur = 1 % m;
+                    let brute = (0..2 * m).find(|_| {
+                        let found = cur == b;
+                        cur = cur * a % m;
+                        found
+                    });
+                    assert_eq!(discrete_log(a, b, m), brute, "{}^x = {} mod {}", a, b, m);
+                }
+            }
+        }
+        assert_eq!(discrete_log(3, 13, 17), Some(4));
+        assert_eq!(discrete_log(2, 0, 1024), Some(10));
+        assert_eq!(discrete_log(2, 3, 1024), None);
+        let p = 998244353;
+        assert_eq!(discrete_log(3, pow_mod(3, 123456789, p), p), Some(123456789));
+        // (10^9 + 7) 2^10, not coprime with 6
+        let m = 1_000_000_007 << 10;
+        assert_eq!(discrete_log(6, pow_mod(6, 100000, m), m), Some(100000));
+        // Above 2^63
+        let m = 3 << 62;
+        assert_eq!(discrete_log(2, 1 << 63, m), Some(63));
+        assert_eq!(discrete_log(2, 3, m), None);
+    }
+
+    #[test]
+    fn test_sqrt_mod() {
+        for p in [2, 3, 5, 7, 13, 17, 97, 257, 7681] {
+            for a in 0..p {
+                let brute = (0..p).find(|&x| x * x % p == a);
+                assert_eq!(sqrt_mod(a, p), brute);
+            }
+        }
+        let p = 998244353;
+        assert_eq!(sqrt_mod(3, p), None);
+        let x = sqrt_mod(123456789, p).unwrap();
+        assert_eq!(mul_mod(x, x, p), 123456789);
+        let p = 1_000_000_000_000_000_003;
+        let x = sqrt_mod(mul_mod(987654321987, 987654321987, p), p).unwrap();
+        assert!(x == 987654321987 || x == p - 987654321987);
+    }
+
+    #[test]
+    fn test_primitive_root() {
+        let sieve: GenericSieve<u64, 100000> = GenericSieve::new();
+        for &p in sieve.primes.iter().take_while(|&&p| p < 1000) {
+            let g = primitive_root(p, &sieve);
+            let order = (1..p).find(|&e| pow_mod(g, e, p) == 1);
+            assert_eq!(order, Some(p - 1));
+            assert!((1..g).all(|h| (1..p - 1).any(|e| pow_mod(h, e, p) == 1)));
+        }
+        assert_eq!(primitive_root(998244353, &sieve), 3);
+        assert_eq!(primitive_root(1_000_000_007, &sieve), 5);
+        assert_eq!(primitive_root(754974721, &sieve), 11);
+    }
+}
@@ -0,0 +1,199 @@
+// ----- Persistent Segment Tree -----
+// Every update returns a new version and copies only the O(log n) nodes on the
+// path to the updated leaf, the rest are shared with the previous version.
+// Nodes live in an arena and link to their children by index.
+use {
+    std::ops::Range,
+    crate::segment_tree::{Monoid, Sum},
+};
+
+struct Node<S> {
+    value: S,
+    left: usize,
+    right: usize,
+}
+
+pub struct PersistentSegTree<M: Monoid> {
+    fr: usize,
+    to: usize,
+    nodes: Vec<Node<M::S>>,
+    roots: Vec<usize>,
+}
+
+impl<M: Monoid> PersistentSegTree<M> {
+    /// Version 0 over positions [fr, to], all of them set to initial_value.
+    pub fn new(fr: usize, to: usize, initial_value: M::S) -> PersistentSegTree<M> {
+        debug_assert!(fr <= to);
+        PersistentSegTree::build(fr, to, &|_| initial_value.clone())
+    }
+
+    /// Version 0 over positions [0, values.len()).
+    pub fn from_slice(values: &[M::S]) -> PersistentSegTree<M> {
+        debug_assert!(!values.is_empty());
+        PersistentSegTree::build(0, values.len() - 1, &|i| values[i].clone())
+    }
+
+    fn build(fr: usize, to: usize, value_at: &impl Fn(usize) -> M::S) -> PersistentSegTree<M> {
+        let mut stree = PersistentSegTree { fr, to, nodes: Vec::new(), roots: Vec::new() };
+        let root = stree.build_rec(fr, to, value_at);
+        stree.roots.push(root);
+        stree
+    }
+
+    fn build_rec(&mut self, fr: usize, to: usize, value_at: &impl Fn(usize) -> M::S) -> usize {
+        if fr == to {
+            return self.push_node(value_at(fr), 0, 0);
+        }
+        let mid = fr + (to - fr) / 2;
+        let left = self.build_rec(fr, mid, value_at);
+        let right = self.build_rec(mid + 1, to, value_at);
+        self.push_pair(left, right)
+    }
+
+    fn push_node(&mut self, value: M::S, left: usize, right: usize) -> usize {
+        self.nodes.push(Node { value, left, right });
+        self.nodes.len() - 1
+    }
+
+    fn push_pair(&mut self, left: usize, right: usize) -> usize {
+        let value = M::op(&self.nodes[left].value, &self.nodes[right].value);
+        self.push_node(value, left, right)
+    }
+
+    /// Number of versions, the latest one is versions() - 1.
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Combination of the values in [a, b] as of the given version.
+    pub fn query(&self, version: usize, a: usize, b: usize) -> M::S {
+        debug_assert!(self.fr <= a && a <= b && b <= self.to);
+        self.query_rec(self.roots[version], self.fr, self.to, a, b)
+    }
+
+    fn query_rec(&self, k: usize, fr: usize, to: usize, a: usize, b: usize) -> M::S {
+        if a == fr && b == to {
+            return self.nodes[k].value.clone();
+        }
+        let mid = fr + (to - fr) / 2;
+        let node = &self.nodes[k];
+        if mid >= b {
+            self.query_rec(node.left, fr, mid, a, b)
+        } else if mid < a {
+            self.query_rec(node.right, mid + 1, to, a, b)
+        } else {
+            M::op(&self.query_rec(node.left, fr, mid, a, mid), &self.query_rec(node.right, mid + 1, to, mid + 1, b))
+        }
+    }
+
+    /// Combination of the values in [r.start, r.end) as of the given version,
+    /// the identity if empty.
+    pub fn query_range(&self, version: usize, r: Range<usize>) -> M::S {
+        if r.is_empty() { M::identity() } else { self.query(version, r.start, r.end - 1) }
+    }
+
+    pub fn get(&self, version: usize, i: usize) -> M::S {
+        self.query(version, i, i)
+    }
+
+    /// New version equal to the given one except for position i, which is set
+    /// to v. Returns the new version.
+    pub fn update(&mut self, version: usize, i: usize, v: M::S) -> usize {
+        debug_assert!(self.fr <= i && i <= self.to);
+        let root = self.update_rec(self.roots[version], self.fr, self.to, i, v);
+        self.roots.push(root);
+        self.roots.len() - 1
+    }
+
+    fn update_rec(&mut self, k: usize, fr: usize, to: usize, i: usize, v: M::S) -> usize {
+        if fr == to {
+            return self.push_node(v, 0, 0);
+        }
+        let mid = fr + (to - fr) / 2;
+        let Node { left, right, .. } = self.nodes[k];
+        if i <= mid {
+            let left = self.update_rec(left, fr, mid, i, v);
+            self.push_pair(left, right)
+        } else {
+            let right = self.update_rec(right, mid + 1, to, i, v);
+            self.push_pair(left, right)
+        }
+    }
+}
+
+impl PersistentSegTree<Sum<usize>> {
+    /// Counts per position, returns the position of the k-th (0-indexed)
+    /// element counted in version new and not in version old.
+    /// For the k-th smallest of a[l..r]: positions are the ranks of the values,
+    /// version i + 1 adds one at the rank of a[i], then kth(l, r, k).
+    pub fn kth(&self, old: usize, new: usize, mut k: usize) -> usize {
+        let (mut a, mut b) = (self.roots[old], self.roots[new]);
+        debug_assert!(k < self.nodes[b].value - self.nodes[a].value);
+        let (mut fr, mut to) = (self.fr, self.to);
+        while fr < to {
+            let mid = fr + (to - fr) / 2;
+            let (left_a, left_b) = (self.nodes[a].left, self.nodes[b].left);
+            let count = self.nodes[left_b].value - self.nodes[left_a].value;
+            if k < count {
+                (a, b, to) = (left_a, left_b, mid);
+            } else {
+                k -= count;
+                (a, b, fr) = (self.nodes[a].right, self.nodes[b].right, mid + 1);
+            }
+        }
+        fr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use crate::segment_tree::Min;
+
+    #[test]
+    fn test_persistent_seg_tree() {
+        let mut rng = Rng(88172645463325252);
+        let (fr, to) = (3, 40);
+        let mut stree = PersistentSegTree::<Min<i32>>::new(fr, to, 100);
+        let mut history = vec![vec![100; to + 1]];
+        for _ in 0..300 {
+            let version = rng.next(stree.versions());
+            let i = fr + rng.next(to - fr + 1);
+            let v = rng.next(200) as i32 - 100;
+            assert_eq!(stree.update(version, i, v), history.len());
+            let mut values = history[version].clone();
+            values[i] = v;
+            history.push(values);
+
+            let version = rng.next(stree.versions());
+            let a = fr + rng.next(to - fr + 1);
+            let b = a + rng.next(to - a + 1);
+            let expected = *history[version][a..=b].iter().min().unwrap();
+            assert_eq!(stree.query(version, a, b), expected);
+            assert_eq!(stree.get(version, b), history[version][b]);
+        }
+        // only the path to the updated leaf is copied
+        assert!(stree.nodes.len() <= 2 * (to - fr + 1) + 300 * 7);
+    }
+
+    #[test]
+    fn test_persistent_seg_tree_kth() {
+        let mut rng = Rng(2463534242);
+        let a: Vec<usize> = (0..50).map(|_| rng.next(20)).collect();
+        let mut stree = PersistentSegTree::<Sum<usize>>::new(0, 19, 0);
+        for (i, &x) in a.iter().enumerate() {
+            let count = stree.get(i, x);
+            stree.update(i, x, count + 1);
+        }
+        for l in 0..a.len() {
+            for r in l + 1..=a.len() {
+                let mut sorted = a[l..r].to_vec();
+                sorted.sort();
+                for (k, &x) in sorted.iter().enumerate() {
+                    assert_eq!(stree.kth(l, r, k), x);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,417 @@
+// ----- Polynomials and formal power series modulo a prime M -----
+// Products go through ntt::convolution, so everything is fastest for an NTT
+// friendly M like 998244353. inv, log, exp, sqrt and pow give the first n terms
+// of the power series by Newton's iteration in O(n log n), division with
+// remainder is O(n log n), multipoint evaluation and interpolation O(n log^2 n).
+use crate::{ntt::convolution, number_theory::sqrt_mod, zn::Zn};
+
+/// Coefficients from x^0 up without trailing zeros, so that equal polynomials
+/// compare equal. The zero polynomial has no coefficients.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Poly<const M: u32>(Vec<Zn<M>>);
+
+impl<const M: u32> Poly<M> {
+    pub fn new(mut coefs: Vec<Zn<M>>) -> Poly<M> {
+        while coefs.last() == Some(&Zn::new(0)) { coefs.pop(); }
+        Poly(coefs)
+    }
+
+    pub fn constant(c: Zn<M>) -> Poly<M> {
+        Poly::new(vec![c])
+    }
+
+    /// Coefficients of x^0, x^1, ..., x^degree.
+    pub fn coefs(&self) -> &[Zn<M>] {
+        &self.0
+    }
+
+    /// Coefficient of x^i, 0 past the degree.
+    pub fn coef(&self, i: usize) -> Zn<M> {
+        self.0.get(i).copied().unwrap_or_default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// None for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.0.len().checked_sub(1)
+    }
+
+    /// self mod x^n.
+    pub fn truncate(&self, n: usize) -> Poly<M> {
+        Poly::new(self.0[..n.min(self.0.len())].to_vec())
+    }
+
+    /// self * x^k.
+    fn shift_up(&self, k: usize) -> Poly<M> {
+        if self.is_zero() { return Poly::default(); }
+        let mut coefs = vec![Zn::new(0); k];
+        coefs.extend_from_slice(&self.0);
+        Poly(coefs)
+    }
+
+    /// self / x^k, dropping the terms below x^k.
+    fn shift_down(&self, k: usize) -> Poly<M> {
+        Poly(self.0[k.min(self.0.len())..].to_vec())
+    }
+
+    /// Horner's rule, O(n).
+    pub fn eval(&self, x: Zn<M>) -> Zn<M> {
+        self.0.iter().rev().fold(Zn::new(0), |acc, &c| acc * x + c)
+    }
+
+    pub fn derivative(&self) -> Poly<M> {
+        Poly::new(self.0.iter().enumerate().skip(1).map(|(i, &c)| c * i).collect())
+    }
+
+    /// Antiderivative with constant term 0, needs degree + 1 < M.
+    pub fn integral(&self) -> Poly<M> {
+        let n = self.0.len();
+        // 1/i = -(M / i) * 1/(M % i)
+        let mut inv = vec![Zn::new(1); n + 1];
+        for i in 2..=n {
+            inv[i] = -inv[M as usize % i] * (M as usize / i);
+        }
+        let mut coefs = vec![Zn::new(0); n + 1];
+        for i in 0..n {
+            coefs[i + 1] = self.0[i] * inv[i + 1];
+        }
+        Poly::new(coefs)
+    }
+
+    /// 1/self mod x^n, needs self[0] != 0.
+    pub fn inv(&self, n: usize) -> Poly<M> {
+        debug_assert!(self.coef(0) != Zn::new(0));
+        let mut g = Poly::constant(self.coef(0).inverse());
+        let mut m = 1;
+        while m < n {
+            m *= 2;
+            // g = g (2 - f g)
+            let fg = (&self.truncate(m) * &g).truncate(m);
+            g = (&g * &(Poly::constant(Zn::new(2)) - fg)).truncate(m);
+        }
+        g.truncate(n)
+    }
+
+    /// log(self) mod x^n, needs self[0] = 1.
+    pub fn log(&self, n: usize) -> Poly<M> {
+        debug_assert!(self.coef(0) == Zn::new(1));
+        if n == 0 { return Poly::default(); }
+        (&self.truncate(n).derivative() * &self.inv(n)).truncate(n - 1).integral()
+    }
+
+    /// exp(self) mod x^n, needs self[0] = 0.
+    pub fn exp(&self, n: usize) -> Poly<M> {
+        debug_assert!(self.coef(0) == Zn::new(0));
+        let mut g = Poly::constant(Zn::new(1));
+        let mut m = 1;
+        while m < n {
+            m *= 2;
+            // g = g (1 - log g + f)
+            let h = self.truncate(m) - g.log(m) + Poly::constant(Zn::new(1));
+            g = (&g * &h).truncate(m);
+        }
+        g.truncate(n)
+    }
+
+    /// Some g with g^2 = self mod x^n, None if there is none. M odd.
+    pub fn sqrt(&self, n: usize) -> Option<Poly<M>> {
+        // self = x^k f with f[0] != 0, then g = x^(k/2) sqrt(f)
+        let k = match self.0.iter().position(|&c| c != Zn::new(0)) {
+            Some(k) if k < n => k,
+            _ => return Some(Poly::default()),
+        };
+        if k % 2 == 1 { return None; }
+        let f = self.shift_down(k);
+        let len = n - k / 2;
+        let mut g = Poly::constant(Zn::from(sqrt_mod(f.coef(0).value() as u64, M as u64)?));
+        let inv2 = Zn::new(2).inverse();
+        let mut m = 1;
+        while m < len {
+            m *= 2;
+            // g = (g + f / g) / 2
+            g = ((&f.truncate(m) * &g.inv(m)).truncate(m) + g) * inv2;
+        }
+        Some(g.truncate(len).shift_up(k / 2))
+    }
+
+    /// self^k mod x^n.
+    pub fn pow(&self, k: u64, n: usize) -> Poly<M> {
+        if k == 0 { return Poly::constant(Zn::new(1)).truncate(n); }
+        // self = c x^i f with f[0] = 1, then self^k = c^k x^(ik) exp(k log f)
+        let i = match self.0.iter().position(|&c| c != Zn::new(0)) {
+            Some(i) if (i as u128) * (k as u128) < n as u128 => i,
+            _ => return Poly::default(),
+        };
+        let c = self.0[i];
+        let m = n - i * k as usize;
+        let f = self.shift_down(i).truncate(m) * c.inverse();
+        ((f.log(m) * Zn::from(k)).exp(m) * c.pow(k)).shift_up(i * k as usize)
+    }
+
+    /// (q, r) with self = q * other + r and deg r < deg other.
+    pub fn div_rem(&self, other: &Poly<M>) -> (Poly<M>, Poly<M>) {
+        assert!(!other.is_zero(), "Division by the zero polynomial");
+        let (n, m) = (self.0.len(), other.0.len());
+        if n < m { return (Poly::default(), self.clone()); }
+        // Reversing the coefficients turns the division into a product mod x^len
+        let len = n - m + 1;
+        let reverse = |p: &Poly<M>| Poly::new(p.0.iter().rev().copied().collect());
+        let mut q = (&reverse(self).truncate(len) * &reverse(other).inv(len)).0;
+        q.resize(len, Zn::new(0));
+        q.reverse();
+        let q = Poly::new(q);
+        let r = self - &(&q * other);
+        (q, r)
+    }
+
+    /// self(x) for each x in xs, O(n log^2 n) for n = max(deg, len(xs)).
+    pub fn evaluate(&self, xs: &[Zn<M>]) -> Vec<Zn<M>> {
+        if xs.is_empty() { return Vec::new(); }
+        let tree = subproduct_tree(xs);
+        let mut result = vec![Zn::new(0); xs.len()];
+        self.evaluate_rec(&tree, 1, 0, xs.len(), xs, &mut result);
+        result
+    }
+
+    /// self mod tree[node] keeps the values on xs[l..r].
+    fn evaluate_rec(&self, tree: &[Poly<M>], node: usize, l: usize, r: usize, xs: &[Zn<M>], result: &mut [Zn<M>]) {
+        let f = self % &tree[node];
+        if r - l <= 8 {
+            for i in l..r {
+                result[i] = f.eval(xs[i]);
+            }
+            return;
+        }
+        let mid = (l + r) / 2;
+        f.evaluate_rec(tree, 2 * node, l, mid, xs, result);
+        f.evaluate_rec(tree, 2 * node + 1, mid, r, xs, result);
+    }
+
+    /// The polynomial of degree < n through the points (xs[i], ys[i]), the xs
+    /// must be distinct. Lagrange's formula, with the weights 1 / prod(xs[i] - xs[j])
+    /// given by the derivative of the product of all (x - xs[j]).
+    pub fn interpolate(xs: &[Zn<M>], ys: &[Zn<M>]) -> Poly<M> {
+        assert_eq!(xs.len(), ys.len());
+        if xs.is_empty() { return Poly::default(); }
+        let tree = subproduct_tree(xs);
+        let mut weights = vec![Zn::new(0); xs.len()];
+        tree[1].derivative().evaluate_rec(&tree, 1, 0, xs.len(), xs, &mut weights);
+        let c: Vec<Zn<M>> = ys.iter().zip(&weights).map(|(&y, &w)| y / w).collect();
+        interpolate_rec(&tree, 1, 0, xs.len(), &c)
+    }
+}
+
+/// tree[node] = product of (x - xs[i]) over the node's range, with the layout
+/// of segment_tree: node 1 is [0, n) and nodes 2k, 2k + 1 split node k.
+fn subproduct_tree<const M: u32>(xs: &[Zn<M>]) -> Vec<Poly<M>> {
+    fn build<const M: u32>(tree: &mut [Poly<M>], node: usize, l: usize, r: usize, xs: &[Zn<M>]) {
+        if r - l == 1 {
+            tree[node] = Poly::new(vec![-xs[l], Zn::new(1)]);
+            return;
+        }
+        let mid = (l + r) / 2;
+        build(tree, 2 * node, l, mid, xs);
+        build(tree, 2 * node + 1, mid, r, xs);
+        tree[node] = &tree[2 * node] * &tree[2 * node + 1];
+    }
+    let mut tree = vec![Poly::default(); 4 * xs.len()];
+    build(&mut tree, 1, 0, xs.len(), xs);
+    tree
+}
+
+/// sum of c[i] * prod(x - xs[j]) for i in [l, r) and j in [l, r) without i.
+fn interpolate_rec<const M: u32>(tree: &[Poly<M>], node: usize, l: usize, r: usize, c: &[Zn<M>]) -> Poly<M> {
+    if r - l == 1 { return Poly::constant(c[l]); }
+    let mid = (l + r) / 2;
+    let left = interpolate_rec(tree, 2 * node, l, mid, c);
+    let right = interpolate_rec(tree, 2 * node + 1, mid, r, c);
+    left * &tree[2 * node + 1] + right * &tree[2 * node]
+}
+
+impl<const M: u32> std::ops::Add<&Poly<M>> for &Poly<M> {
+    type Output = Poly<M>;
+    fn add(self, other: &Poly<M>) -> Poly<M> {
+        let n = self.0.len().max(other.0.len());
+        Poly::new((0..n).map(|i| self.coef(i) + other.coef(i)).collect())
+    }
+}
+
+impl<const M: u32> std::ops::Sub<&Poly<M>> for &Poly<M> {
+    type Output = Poly<M>;
+    fn sub(self, other: &Poly<M>) -> Poly<M> {
+        let n = self.0.len().max(other.0.len());
+        Poly::new((0..n).map(|i| self.coef(i) - other.coef(i)).collect())
+    }
+}
+
+impl<const M: u32> std::ops::Mul<&Poly<M>> for &Poly<M> {
+    type Output = Poly<M>;
+    fn mul(self, other: &Poly<M>) -> Poly<M> { Poly::new(convolution(&self.0, &other.0)) }
+}
+
+impl<const M: u32> std::ops::Div<&Poly<M>> for &Poly<M> {
+    type Output = Poly<M>;
+    fn div(self, other: &Poly<M>) -> Poly<M> { self.div_rem(other).0 }
+}
+
+impl<const M: u32> std::ops::Rem<&Poly<M>> for &Poly<M> {
+    type Output = Poly<M>;
+    fn rem(self, other: &Poly<M>) -> Poly<M> { self.div_rem(other).1 }
+}
+
+/// Poly op Poly, Poly op &Poly, &Poly op Poly and op=, all through &Poly op &Poly.
+macro_rules! poly_ops {
+    ($($op:ident $f:ident $op_assign:ident $f_assign:ident),*) => {$(
+        impl<const M: u32> std::ops::$op<Poly<M>> for Poly<M> {
+            type Output = Poly<M>;
+            fn $f(self, other: Poly<M>) -> Poly<M> { std::ops::$op::$f(&self, &other) }
+        }
+        impl<const M: u32> std::ops::$op<&Poly<M>> for Poly<M> {
+            type Output = Poly<M>;
+            fn $f(self, other: &Poly<M>) -> Poly<M> { std::ops::$op::$f(&self, other) }
+        }
+        impl<const M: u32> std::ops::$op<Poly<M>> for &Poly<M> {
+            type Output = Poly<M>;
+            fn $f(self, other: Poly<M>) -> Poly<M> { std::ops::$op::$f(self, &other) }
+        }
+        impl<const M: u32> std::ops::$op_assign<&Poly<M>> for Poly<M> {
+            fn $f_assign(&mut self, other: &Poly<M>) { *self = std::ops::$op::$f(&*self, other); }
+        }
+        impl<const M: u32> std::ops::$op_assign<Poly<M>> for Poly<M> {
+            fn $f_assign(&mut self, other: Poly<M>) { *self = std::ops::$op::$f(&*self, &other); }
+        }
+    )*};
+}
+
+poly_ops!(Add add AddAssign add_assign, Sub sub SubAssign sub_assign, Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign, Rem rem RemAssign rem_assign);
+
+impl<const M: u32> std::ops::Mul<Zn<M>> for &Poly<M> {
+    type Output = Poly<M>;
+    fn mul(self, c: Zn<M>) -> Poly<M> { Poly::new(self.0.iter().map(|&x| x * c).collect()) }
+}
+
+impl<const M: u32> std::ops::Mul<Zn<M>> for Poly<M> {
+    type Output = Poly<M>;
+    fn mul(self, c: Zn<M>) -> Poly<M> { &self * c }
+}
+
+impl<const M: u32> std::ops::Neg for Poly<M> {
+    type Output = Poly<M>;
+    fn neg(self) -> Poly<M> { Poly(self.0.into_iter().map(|x| -x).collect()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    type Z = Zn<998244353>;
+    type P = Poly<998244353>;
+
+    fn random(rng: &mut Rng, n: usize) -> Vec<Z> {
+        (0..n).map(|_| Z::new(rng.next(998244353) as i64)).collect()
+    }
+
+    fn poly(coefs: &[i64]) -> P {
+        Poly::new(coefs.iter().map(|&c| Z::new(c)).collect())
+    }
+
+    #[test]
+    fn test_poly_ops() {
+        let a = poly(&[1, 2, 3]);
+        let b = poly(&[-1, 1]);
+        assert_eq!(&a + &b, poly(&[0, 3, 3]));
+        assert_eq!(a.clone() - poly(&[0, 0, 3]), poly(&[1, 2]));
+        assert_eq!(&a * &b, poly(&[-1, -1, -1, 3]));
+        assert_eq!(-b.clone() * Z::new(2), poly(&[2, -2]));
+        assert_eq!((&a - &a).degree(), None);
+        assert_eq!(a.degree(), Some(2));
+        // 3x^2 + 2x + 1 = (3x + 5)(x - 1) + 6
+        assert_eq!(a.div_rem(&b), (poly(&[5, 3]), poly(&[6])));
+        assert_eq!(&b / &a, P::default());
+        assert_eq!(a.eval(Z::new(2)), Z::new(17));
+        assert_eq!(a.derivative(), poly(&[2, 6]));
+        assert_eq!(a.integral().derivative(), a);
+        let mut c = a.clone();
+        c *= &b;
+        c += poly(&[6]);
+        c %= &a;
+        assert_eq!(c, poly(&[6]));
+    }
+
+    #[test]
+    fn test_inv_log_exp() {
+        let mut rng = Rng(88172645463325252);
+        let one = poly(&[1]);
+        for n in [1, 2, 7, 64, 100, 300] {
+            let mut f = random(&mut rng, n);
+            f[0] = Z::new(1);
+            let f = Poly::new(f);
+            assert_eq!((&f * &f.inv(n)).truncate(n), one);
+            assert_eq!(f.log(n).exp(n), f);
+            let mut g = random(&mut rng, n);
+            g[0] = Z::new(0);
+            let g = Poly::new(g);
+            assert_eq!(g.exp(n).log(n), g);
+        }
+        // 1/(1 - x) = 1 + x + x^2 + ..., log(1 - x) = -x - x^2/2 - ...
+        assert_eq!(poly(&[1, -1]).inv(4), poly(&[1, 1, 1, 1]));
+        let expected: Vec<Z> = (0..5).map(|i| if i == 0 { Z::new(0) } else { -Z::new(i).inverse() }).collect();
+        assert_eq!(poly(&[1, -1]).log(5), Poly::new(expected));
+    }
+
+    #[test]
+    fn test_sqrt_pow() {
+        let mut rng = Rng(88172645463325252);
+        for n in [1, 5, 64, 200] {
+            let g = Poly::new(random(&mut rng, n));
+            for k in [0, 2, 4] {
+                let f = (&g * &g).shift_up(k).truncate(n);
+                let s = f.sqrt(n).unwrap();
+                assert_eq!((&s * &s).truncate(n), f);
+            }
+            let mut naive = poly(&[1]);
+            let h = g.shift_up(n / 40);
+            for k in 0..6 {
+                assert_eq!(h.pow(k, n), naive);
+                naive = (&naive * &h).truncate(n);
+            }
+            assert_eq!(h.pow(1 << 40, n).is_zero(), n / 40 > 0);
+        }
+        assert_eq!(poly(&[0, 1]).sqrt(5), None);
+        // 3 is a primitive root, so not a square
+        assert_eq!(poly(&[3, 1]).sqrt(5), None);
+        assert_eq!(poly(&[0, 1]).sqrt(1), Some(P::default()));
+        assert_eq!(poly(&[4]).sqrt(3).map(|s| s.eval(Z::new(0)).pow(2)), Some(Z::new(4)));
+        assert_eq!(poly(&[0, 2]).pow(3, 4), poly(&[0, 0, 0, 8]));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let mut rng = Rng(88172645463325252);
+        for (n, m) in [(1, 1), (10, 3), (200, 70), (300, 299), (500, 1)] {
+            let a = Poly::new(random(&mut rng, n));
+            let b = Poly::new(random(&mut rng, m));
+            let (q, r) = a.div_rem(&b);
+            assert!(r.degree() < b.degree());
+            assert_eq!(&q * &b + r, a);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_interpolate() {
+        let mut rng = Rng(88172645463325252);
+        for n in [1, 2, 9, 100, 257] {
+            let f = Poly::new(random(&mut rng, n));
+            let xs: Vec<Z> = (0..n).map(|i| Z::new(i as i64 * 1000 - 3)).collect();
+            let ys = f.evaluate(&xs);
+            assert_eq!(ys, xs.iter().map(|&x| f.eval(x)).collect::<Vec<Z>>());
+            assert_eq!(Poly::interpolate(&xs, &ys), f);
+        }
+        assert_eq!(poly(&[1, 1]).evaluate(&[]), []);
+    }
+}
@@ -0,0 +1,181 @@
+// ----- Fast input scanner -----
+use std::io::Read;
+
+/// Reads the whole input at once and hands out whitespace separated tokens.
+pub struct Scanner {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+/// Types that can be read from a `Scanner`.
+pub trait Readable {
+    fn read(sc: &mut Scanner) -> Self;
+}
+
+impl Scanner {
+    /// Scanner over all of stdin.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Scanner {
+        let mut buf = vec![];
+        std::io::stdin().read_to_end(&mut buf).expect("Failed to read stdin");
+        Scanner::from_bytes(buf)
+    }
+
+    pub fn from_bytes(buf: Vec<u8>) -> Scanner {
+        Scanner { buf, pos: 0 }
+    }
+
+    /// Next token, panics if there are no tokens left.
+    pub fn token(&mut self) -> &[u8] {
+        while self.pos < self.buf.len() && self.buf[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        let start = self.pos;
+        while self.pos < self.buf.len() && !self.buf[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        assert!(start < self.pos, "No tokens left in the input");
+        &self.buf[start..self.pos]
+    }
+
+    pub fn read<T: Readable>(&mut self) -> T {
+        T::read(self)
+    }
+
+    pub fn vec<T: Readable>(&mut self, n: usize) -> Vec<T> {
+        (0..n).map(|_| self.read()).collect()
+    }
+
+    /// vec1(3) on "7 8 9" = [0, 7, 8, 9]
+    pub fn vec1<T: Readable + Default>(&mut self, n: usize) -> Vec<T> {
+        std::iter::once(T::default()).chain((0..n).map(|_| self.read())).collect()
+    }
+
+    pub fn bytes(&mut self) -> Vec<u8> {
+        self.token().to_vec()
+    }
+
+    /// h rows of bytes, e.g. a map of '#' and '.'
+    pub fn grid(&mut self, h: usize) -> Vec<Vec<u8>> {
+        (0..h).map(|_| self.bytes()).collect()
+    }
+}
+
+macro_rules! readable_int {
+    ($($t:ty)*) => {$(
+        impl Readable for $t {
+            fn read(sc: &mut Scanner) -> $t {
+                let token = sc.token();
+                let (neg, digits) = match token[0] {
+                    b'-' => (true, &token[1..]),
+                    _ => (false, token),
+                };
+                let mut x: $t = 0;
+                for &d in digits {
+                    debug_assert!(d.is_ascii_digit(), "Invalid integer {:?}", token);
+                    let d = (d - b'0') as $t;
+                    // accumulate negatives as negatives so that MIN fits
+                    x = if neg { x * 10 - d } else { x * 10 + d };
+                }
+                x
+            }
+        }
+    )*};
+}
+
+readable_int!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+impl Readable for f64 {
+    fn read(sc: &mut Scanner) -> f64 {
+        std::str::from_utf8(sc.token()).unwrap().parse().unwrap()
+    }
+}
+
+impl Readable for char {
+    fn read(sc: &mut Scanner) -> char {
+        sc.token()[0] as char
+    }
+}
+
+impl Readable for String {
+    fn read(sc: &mut Scanner) -> String {
+        String::from_utf8(sc.bytes()).unwrap()
+    }
+}
+
+macro_rules! readable_tuple {
+    ($($t:ident)*) => {
+        impl<$($t: Readable),*> Readable for ($($t,)*) {
+            fn read(sc: &mut Scanner) -> Self {
+                ($($t::read(sc),)*)
+            }
+        }
+    };
+}
+
+readable_tuple!(A B);
+readable_tuple!(A B C);
+readable_tuple!(A B C D);
+
+/// input!(sc, n: usize, a: [i64; n], s: Bytes, p: (usize, Usize1));
+/// Types must be a single token tree, `Usize1` reads a 1-indexed position as
+/// 0-indexed and `Bytes` reads a token as `Vec<u8>`.
+#[macro_export]
+macro_rules! input {
+    ($sc:expr $(,)?) => {};
+    ($sc:expr, mut $x:ident : $t:tt $($rest:tt)*) => {
+        let mut $x = $crate::read_value!($sc, $t);
+        $crate::input!($sc $($rest)*);
+    };
+    ($sc:expr, $x:ident : $t:tt $($rest:tt)*) => {
+        let $x = $crate::read_value!($sc, $t);
+        $crate::input!($sc $($rest)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! read_value {
+    ($sc:expr, [$t:tt; $n:expr]) => {
+        (0..$n).map(|_| $crate::read_value!($sc, $t)).collect::<Vec<_>>()
+    };
+    ($sc:expr, ($($t:tt),*)) => { ($($crate::read_value!($sc, $t),)*) };
+    ($sc:expr, Bytes) => { $sc.bytes() };
+    ($sc:expr, Usize1) => { $sc.read::<usize>() - 1 };
+    ($sc:expr, $t:ty) => { $sc.read::<$t>() };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let input = "3\n-12 7 9223372036854775807\r\n-9223372036854775808 2.5 x\n\
+                     1 2 3 a 4\n..#\n#..\nword";
+        let mut sc = Scanner::from_bytes(input.as_bytes().to_vec());
+        let n: usize = sc.read();
+        assert_eq!(n, 3);
+        assert_eq!(sc.vec::<i64>(n), vec![-12, 7, i64::MAX]);
+        let t: (i64, f64, char) = sc.read();
+        assert_eq!(t, (i64::MIN, 2.5, 'x'));
+        assert_eq!(sc.vec1::<u8>(2), vec![0, 1, 2]);
+        let t: (u32, String, i32) = sc.read();
+        assert_eq!(t, (3, String::from("a"), 4));
+        assert_eq!(sc.grid(2), vec![b"..#".to_vec(), b"#..".to_vec()]);
+        assert_eq!(sc.bytes(), b"word");
+    }
+
+    #[test]
+    fn test_input_macro() {
+        let input = "3 -1 5 2\nab 1 3\n4 2\n";
+        let mut sc = Scanner::from_bytes(input.as_bytes().to_vec());
+        input!(sc, n: usize, mut a: [i64; n], s: Bytes, p: (usize, Usize1));
+        a.push(0);
+        input!(sc, g: [[u8; 2]; 1],);
+        assert_eq!(a, vec![-1, 5, 2, 0]);
+        assert_eq!(s, b"ab");
+        assert_eq!(p, (1, 2));
+        assert_eq!(g, vec![vec![4, 2]]);
+    }
+}
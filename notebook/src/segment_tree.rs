@@ -1,31 +1,108 @@
 // -----Segment Tree -----
-pub struct SegTree<T> {
+use std::{marker::PhantomData, ops::Range};
+
+/// Associative operation with an identity element.
+pub trait Monoid {
+    type S: Clone;
+    fn identity() -> Self::S;
+    fn op(a: &Self::S, b: &Self::S) -> Self::S;
+}
+
+/// Integers with a minimum and a maximum value, the identities of max and min.
+pub trait Bounded {
+    const MIN: Self;
+    const MAX: Self;
+}
+
+macro_rules! bounded {
+    ($($t:ty)*) => {$(
+        impl Bounded for $t {
+            const MIN: $t = <$t>::MIN;
+            const MAX: $t = <$t>::MAX;
+        }
+    )*};
+}
+
+bounded!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+pub struct Sum<T>(PhantomData<T>);
+pub struct Min<T>(PhantomData<T>);
+pub struct Max<T>(PhantomData<T>);
+pub struct Gcd<T>(PhantomData<T>);
+pub struct Xor<T>(PhantomData<T>);
+
+impl<T: Copy + Default + std::ops::Add<Output = T>> Monoid for Sum<T> {
+    type S = T;
+    fn identity() -> T { T::default() }
+    fn op(a: &T, b: &T) -> T { *a + *b }
+}
+
+impl<T: Copy + Ord + Bounded> Monoid for Min<T> {
+    type S = T;
+    fn identity() -> T { T::MAX }
+    fn op(a: &T, b: &T) -> T { *a.min(b) }
+}
+
+impl<T: Copy + Ord + Bounded> Monoid for Max<T> {
+    type S = T;
+    fn identity() -> T { T::MIN }
+    fn op(a: &T, b: &T) -> T { *a.max(b) }
+}
+
+/// Non negative integers, gcd(0, x) = x.
+impl<T: Copy + Default + PartialEq + std::ops::Rem<Output = T>> Monoid for Gcd<T> {
+    type S = T;
+    fn identity() -> T { T::default() }
+    fn op(a: &T, b: &T) -> T {
+        let (mut a, mut b) = (*a, *b);
+        while b != T::default() {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+}
+
+impl<T: Copy + Default + std::ops::BitXor<Output = T>> Monoid for Xor<T> {
+    type S = T;
+    fn identity() -> T { T::default() }
+    fn op(a: &T, b: &T) -> T { *a ^ *b }
+}
+
+pub struct SegTree<M: Monoid> {
     fr: usize,
     to: usize,
-    value: T,
-    left: Option<Box<SegTree<T>>>,
-    right: Option<Box<SegTree<T>>>,
+    value: M::S,
+    left: Option<Box<SegTree<M>>>,
+    right: Option<Box<SegTree<M>>>,
 }
 
-impl<T> SegTree<T>
-where
-    T: Clone + std::ops::Add<Output = T> + Copy,
-{
-    pub fn new(fr: usize, to: usize, initial_value: T) -> SegTree<T> {
+impl<M: Monoid> SegTree<M> {
+    /// Tree over positions [fr, to], all of them set to initial_value.
+    pub fn new(fr: usize, to: usize, initial_value: M::S) -> SegTree<M> {
         debug_assert!(fr <= to);
+        SegTree::build(fr, to, &|_| initial_value.clone())
+    }
+
+    /// Tree over positions [0, values.len()).
+    pub fn from_slice(values: &[M::S]) -> SegTree<M> {
+        debug_assert!(!values.is_empty());
+        SegTree::build(0, values.len() - 1, &|i| values[i].clone())
+    }
+
+    fn build(fr: usize, to: usize, value_at: &impl Fn(usize) -> M::S) -> SegTree<M> {
         if fr == to {
             return SegTree {
                 fr,
                 to,
-                value: initial_value,
+                value: value_at(fr),
                 left: None,
                 right: None,
             };
         }
         let mid = fr + (to - fr) / 2;
-        let left = SegTree::new(fr, mid, initial_value);
-        let right = SegTree::new(mid + 1, to, initial_value);
-        let value = left.value + right.value;
+        let left = SegTree::build(fr, mid, value_at);
+        let right = SegTree::build(mid + 1, to, value_at);
+        let value = M::op(&left.value, &right.value);
         let left = Some(Box::new(left));
         let right = Some(Box::new(right));
         SegTree {
@@ -37,10 +114,11 @@ where
         }
     }
 
-    pub fn query(&self, a: usize, b: usize) -> T {
+    /// Combination of the values in [a, b].
+    pub fn query(&self, a: usize, b: usize) -> M::S {
         debug_assert!(self.fr <= a && a <= b && b <= self.to);
         if a == self.fr && b == self.to {
-            return self.value;
+            return self.value.clone();
         }
         let left = self.left.as_ref().unwrap();
         let right = self.right.as_ref().unwrap();
@@ -49,11 +127,16 @@ where
         } else if right.fr <= a {
             right.query(a, b)
         } else {
-            left.query(a, left.to) + right.query(right.fr, b)
+            M::op(&left.query(a, left.to), &right.query(right.fr, b))
         }
     }
 
-    pub fn update(&mut self, i: usize, v: T) {
+    /// Combination of the values in [r.start, r.end), the identity if empty.
+    pub fn query_range(&self, r: Range<usize>) -> M::S {
+        if r.is_empty() { M::identity() } else { self.query(r.start, r.end - 1) }
+    }
+
+    pub fn update(&mut self, i: usize, v: M::S) {
         debug_assert!(self.fr <= i && i <= self.to);
         if self.fr == self.to {
             self.value = v;
@@ -66,7 +149,81 @@ where
         } else {
             right.update(i, v);
         }
-        self.value = left.value + right.value;
+        self.value = M::op(&left.value, &right.value);
+    }
+
+    /// Largest r such that f(op of [l, r)) holds, e.g. the first position
+    /// where a prefix sum exceeds k. f must hold for the identity and be
+    /// monotone: once it fails it keeps failing as r grows.
+    pub fn max_right(&self, l: usize, f: impl Fn(&M::S) -> bool) -> usize {
+        debug_assert!(self.fr <= l && l <= self.to + 1 && f(&M::identity()));
+        let mut acc = M::identity();
+        self.max_right_rec(l, &f, &mut acc).unwrap_or(self.to + 1)
+    }
+
+    fn max_right_rec(&self, l: usize, f: &impl Fn(&M::S) -> bool, acc: &mut M::S) -> Option<usize> {
+        if self.to < l {
+            return None;
+        }
+        if l <= self.fr {
+            let value = M::op(acc, &self.value);
+            if f(&value) {
+                *acc = value;
+                return None;
+            }
+            if self.fr == self.to {
+                return Some(self.fr);
+            }
+        }
+        let left = self.left.as_ref().unwrap();
+        let right = self.right.as_ref().unwrap();
+        left.max_right_rec(l, f, acc).or_else(|| right.max_right_rec(l, f, acc))
+    }
+
+    /// Smallest l such that f(op of [l, r)) holds. f must hold for the
+    /// identity and be monotone: once it fails it keeps failing as l decreases.
+    pub fn min_left(&self, r: usize, f: impl Fn(&M::S) -> bool) -> usize {
+        debug_assert!(self.fr <= r && r <= self.to + 1 && f(&M::identity()));
+        let mut acc = M::identity();
+        self.min_left_rec(r, &f, &mut acc).unwrap_or(self.fr)
+    }
+
+    fn min_left_rec(&self, r: usize, f: &impl Fn(&M::S) -> bool, acc: &mut M::S) -> Option<usize> {
+        if r <= self.fr {
+            return None;
+        }
+        if self.to < r {
+            let value = M::op(&self.value, acc);
+            if f(&value) {
+                *acc = value;
+                return None;
+            }
+            if self.fr == self.to {
+                return Some(self.fr + 1);
+            }
+        }
+        let left = self.left.as_ref().unwrap();
+        let right = self.right.as_ref().unwrap();
+        right.min_left_rec(r, f, acc).or_else(|| left.min_left_rec(r, f, acc))
+    }
+}
+
+/// One line per level of the tree, with the range and value of each node.
+#[cfg(feature = "local")]
+impl<M: Monoid> std::fmt::Debug for SegTree<M> where M::S: std::fmt::Debug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut level = vec![self];
+        while !level.is_empty() {
+            write!(f, "\n ")?;
+            for node in &level {
+                write!(f, " [{}, {}]: {:?}", node.fr, node.to, node.value)?;
+            }
+            level = level.iter()
+                .flat_map(|node| [node.left.as_deref(), node.right.as_deref()])
+                .flatten()
+                .collect();
+        }
+        Ok(())
     }
 }
 
@@ -76,7 +233,7 @@ mod tests {
 
     #[test]
     fn test_seg_tree() {
-        let mut stree = SegTree::new(0, 9, 0);
+        let mut stree = SegTree::<Sum<i32>>::new(0, 9, 0);
         assert_eq!(0, stree.query(0, 9));
         assert_eq!(0, stree.query(4, 4));
         assert_eq!(0, stree.query(9, 9));
@@ -90,4 +247,46 @@ mod tests {
         assert_eq!(20, stree.query(1, 9));
         assert_eq!(16, stree.query(2, 8));
     }
+
+    #[test]
+    fn test_seg_tree_monoids() {
+        let v: Vec<u32> = vec![12, 18, 7, 30, 6, 9];
+        let min = SegTree::<Min<u32>>::from_slice(&v);
+        let max = SegTree::<Max<u32>>::from_slice(&v);
+        let gcd = SegTree::<Gcd<u32>>::from_slice(&v);
+        let mut xor = SegTree::<Xor<u32>>::from_slice(&v);
+        for a in 0..v.len() {
+            for b in a..=v.len() {
+                let r = &v[a..b];
+                assert_eq!(min.query_range(a..b), r.iter().copied().min().unwrap_or(u32::MAX));
+                assert_eq!(max.query_range(a..b), r.iter().copied().max().unwrap_or(u32::MIN));
+                assert_eq!(gcd.query_range(a..b), r.iter().fold(0, |g, &x| Gcd::<u32>::op(&g, &x)));
+                assert_eq!(xor.query_range(a..b), r.iter().fold(0, |g, &x| g ^ x));
+            }
+        }
+        assert_eq!(gcd.query(0, 1), 6);
+        assert_eq!(gcd.query(3, 5), 3);
+        xor.update(2, 0);
+        assert_eq!(xor.query(1, 3), 18 ^ 30);
+    }
+
+    #[test]
+    fn test_seg_tree_binary_search() {
+        let v: Vec<u32> = vec![3, 0, 4, 1, 5, 9, 2, 6];
+        let stree = SegTree::<Sum<u32>>::from_slice(&v);
+        for k in 0..35 {
+            for l in 0..=v.len() {
+                let expected = (l..=v.len()).rev().find(|&r| v[l..r].iter().sum::<u32>() <= k).unwrap();
+                assert_eq!(stree.max_right(l, |&s| s <= k), expected);
+            }
+            for r in 0..=v.len() {
+                let expected = (0..=r).find(|&l| v[l..r].iter().sum::<u32>() <= k).unwrap();
+                assert_eq!(stree.min_left(r, |&s| s <= k), expected);
+            }
+        }
+        // leftmost position with value >= 5
+        let max = SegTree::<Max<u32>>::from_slice(&v);
+        assert_eq!(max.max_right(0, |&m| m < 5), 4);
+        assert_eq!(max.min_left(4, |&m| m < 4), 3);
+    }
 }
@@ -13,6 +13,7 @@ Copy + Ord + std::fmt::Debug
 <T as TryFrom<usize>>::Error: std::fmt::Debug,
 <T as TryInto<usize>>::Error: std::fmt::Debug
 {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> GenericSieve<T,N> {
         let mut is_p = vec![true; N];
         let mut primes = vec![];
@@ -53,10 +54,10 @@ Copy + Ord + std::fmt::Debug
         let mut pf: Vec<T> = Vec::new();
         if n < N.try_into().unwrap() { return self.factorize_small_n(n, pf); }
         for p in &self.primes {
-            if p.clone()*p.clone() > n { break; }
-            while n % p.clone() == 0.try_into().unwrap() {
-                pf.push(p.clone());
-                n = n / p.clone();
+            if *p * *p > n { break; }
+            while n % *p == 0.try_into().unwrap() {
+                pf.push(*p);
+                n = n / *p;
                 if n < N.try_into().unwrap() { return self.factorize_small_n(n, pf); }
             }
         }
@@ -0,0 +1,141 @@
+// ----- Sparse Table -----
+// Static range queries in O(1) after an O(n log n) build, positions [0, n).
+use {
+    std::ops::Range,
+    crate::segment_tree::{Bounded, Gcd, Max, Min, Monoid},
+};
+
+/// Monoids where op(x, x) = x, so overlapping ranges can be combined.
+pub trait Idempotent: Monoid {}
+
+impl<T: Copy + Ord + Bounded> Idempotent for Min<T> {}
+impl<T: Copy + Ord + Bounded> Idempotent for Max<T> {}
+impl<T: Copy + Default + PartialEq + std::ops::Rem<Output = T>> Idempotent for Gcd<T> {}
+
+/// table[k][i] is the combination of the values in [i, i + 2^k).
+pub struct SparseTable<M: Idempotent> {
+    table: Vec<Vec<M::S>>,
+}
+
+impl<M: Idempotent> SparseTable<M> {
+    pub fn from_slice(values: &[M::S]) -> SparseTable<M> {
+        debug_assert!(!values.is_empty());
+        let mut table = vec![values.to_vec()];
+        let mut k = 1;
+        while 2 * k <= values.len() {
+            let prev = table.last().unwrap();
+            let row = (0..prev.len() - k).map(|i| M::op(&prev[i], &prev[i + k])).collect();
+            table.push(row);
+            k *= 2;
+        }
+        SparseTable { table }
+    }
+
+    /// Combination of the values in [a, b], as that of two overlapping ranges.
+    pub fn query(&self, a: usize, b: usize) -> M::S {
+        debug_assert!(a <= b && b < self.table[0].len());
+        let k = (b - a + 1).ilog2() as usize;
+        M::op(&self.table[k][a], &self.table[k][b + 1 - (1 << k)])
+    }
+
+    /// Combination of the values in [r.start, r.end), the identity if empty.
+    pub fn query_range(&self, r: Range<usize>) -> M::S {
+        if r.is_empty() { M::identity() } else { self.query(r.start, r.end - 1) }
+    }
+}
+
+/// O(1) queries for any monoid, e.g. sums or products with Zn. Row h splits
+/// the positions into blocks of size 2^h, each cell holds the combination from
+/// itself to the middle of its block, so [a, b] is answered by the row where
+/// a and b fall in different halves of the same block.
+pub struct DisjointSparseTable<M: Monoid> {
+    table: Vec<Vec<M::S>>,
+}
+
+impl<M: Monoid> DisjointSparseTable<M> {
+    pub fn from_slice(values: &[M::S]) -> DisjointSparseTable<M> {
+        debug_assert!(!values.is_empty());
+        let size = values.len().next_power_of_two().max(2);
+        let mut values = values.to_vec();
+        values.resize(size, M::identity());
+        let mut table = vec![values.clone()];
+        let mut half = 1;
+        while half < size {
+            let mut row = values.clone();
+            for mid in (half..size).step_by(2 * half) {
+                for i in (mid - half..mid - 1).rev() {
+                    row[i] = M::op(&values[i], &row[i + 1]);
+                }
+                for i in mid + 1..mid + half {
+                    row[i] = M::op(&row[i - 1], &values[i]);
+                }
+            }
+            table.push(row);
+            half *= 2;
+        }
+        DisjointSparseTable { table }
+    }
+
+    /// Combination of the values in [a, b].
+    pub fn query(&self, a: usize, b: usize) -> M::S {
+        debug_assert!(a <= b && b < self.table[0].len());
+        if a == b {
+            return self.table[0][a].clone();
+        }
+        let h = ((a ^ b).ilog2() + 1) as usize;
+        M::op(&self.table[h][a], &self.table[h][b])
+    }
+
+    /// Combination of the values in [r.start, r.end), the identity if empty.
+    pub fn query_range(&self, r: Range<usize>) -> M::S {
+        if r.is_empty() { M::identity() } else { self.query(r.start, r.end - 1) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{Rng, Concat};
+    use crate::{segment_tree::Sum, zn::Zn};
+
+    #[test]
+    fn test_sparse_table() {
+        let mut rng = Rng(88172645463325252);
+        for n in 1..40 {
+            let v: Vec<u32> = (0..n).map(|_| rng.next(100) as u32).collect();
+            let min = SparseTable::<Min<u32>>::from_slice(&v);
+            let max = SparseTable::<Max<u32>>::from_slice(&v);
+            let gcd = SparseTable::<Gcd<u32>>::from_slice(&v);
+            for a in 0..n {
+                for b in a..=n {
+                    let r = &v[a..b];
+                    assert_eq!(min.query_range(a..b), r.iter().copied().min().unwrap_or(u32::MAX));
+                    assert_eq!(max.query_range(a..b), r.iter().copied().max().unwrap_or(u32::MIN));
+                    assert_eq!(gcd.query_range(a..b), r.iter().fold(0, |g, &x| Gcd::<u32>::op(&g, &x)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_disjoint_sparse_table() {
+        let mut rng = Rng(2463534242);
+        type Z = Zn<998244353>;
+        for n in 1..40 {
+            let v: Vec<Z> = (0..n).map(|_| Z::new(rng.next(1 << 30) as i64)).collect();
+            let sum = DisjointSparseTable::<Sum<Z>>::from_slice(&v);
+            for a in 0..n {
+                for b in a..=n {
+                    assert_eq!(sum.query_range(a..b), v[a..b].iter().fold(Z::new(0), |s, &x| s + x));
+                }
+            }
+        }
+        let letters: Vec<String> = "abcdefghij".chars().map(String::from).collect();
+        let concat = DisjointSparseTable::<Concat>::from_slice(&letters);
+        for a in 0..letters.len() {
+            for b in a..=letters.len() {
+                assert_eq!(concat.query_range(a..b), "abcdefghij"[a..b]);
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// ----- Test helpers -----
+// Shared by the tests of the notebook structures.
+use crate::segment_tree::Monoid;
+
+/// xorshift64, deterministic so that failures can be reproduced.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform enough in [0, n).
+    pub fn next(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Concatenation, to check that the order of the operands is kept.
+pub struct Concat;
+
+impl Monoid for Concat {
+    type S = String;
+    fn identity() -> String { String::new() }
+    fn op(a: &String, b: &String) -> String { a.clone() + b }
+}
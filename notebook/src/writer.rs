@@ -0,0 +1,102 @@
+// ----- Buffered output writer -----
+use std::{
+    fmt::Display,
+    io::{self, BufWriter, Stdout, Write},
+};
+
+/// Buffered writer, the output is flushed when it's dropped (i.e. at the end
+/// of main). Avoid `std::process::exit`, it skips the flush.
+pub struct Writer<W: Write = Stdout> {
+    out: BufWriter<W>,
+}
+
+impl Writer<Stdout> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Writer<Stdout> {
+        Writer::from_writer(io::stdout())
+    }
+}
+
+impl<W: Write> Writer<W> {
+    pub fn from_writer(w: W) -> Writer<W> {
+        Writer { out: BufWriter::new(w) }
+    }
+
+    pub fn print<T: Display>(&mut self, x: T) {
+        write!(self.out, "{}", x).unwrap();
+    }
+
+    pub fn println<T: Display>(&mut self, x: T) {
+        writeln!(self.out, "{}", x).unwrap();
+    }
+
+    /// join(&[1, 2, 3]) writes "1 2 3\n"
+    pub fn join<T: Display>(&mut self, v: &[T]) {
+        self.join_with(v, " ");
+    }
+
+    pub fn join_with<T: Display>(&mut self, v: &[T], sep: &str) {
+        for (i, x) in v.iter().enumerate() {
+            if i > 0 { self.out.write_all(sep.as_bytes()).unwrap(); }
+            write!(self.out, "{}", x).unwrap();
+        }
+        writeln!(self.out).unwrap();
+    }
+
+    pub fn yes_no(&mut self, b: bool) {
+        self.println(if b { "Yes" } else { "No" });
+    }
+
+    pub fn yes_no_upper(&mut self, b: bool) {
+        self.println(if b { "YES" } else { "NO" });
+    }
+
+    pub fn flush(&mut self) {
+        self.out.flush().unwrap();
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().ok().unwrap()
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// out!(w, a, b, c) writes "a b c\n"
+#[macro_export]
+macro_rules! out {
+    ($w:expr) => { $w.println("") };
+    ($w:expr, $first:expr $(, $x:expr)* $(,)?) => {{
+        $w.print(&$first);
+        $($w.print(" "); $w.print(&$x);)*
+        $w.println("");
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer() {
+        let mut w = Writer::from_writer(vec![]);
+        w.join(&[1, 2, 3]);
+        w.join_with(&['a', 'b'], ", ");
+        w.join::<u32>(&[]);
+        w.yes_no(true);
+        w.yes_no_upper(false);
+        out!(w, 7, "x", 2.5);
+        out!(w);
+        writeln!(w, "{}-{}", 4, 5).unwrap();
+        let output = String::from_utf8(w.into_inner()).unwrap();
+        assert_eq!(output, "1 2 3\na, b\n\nYes\nNO\n7 x 2.5\n\n4-5\n");
+    }
+}
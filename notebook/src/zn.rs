@@ -1,12 +1,154 @@
-#[derive(Copy, Clone, Debug)]
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// Integers modulo M, the value is always in [0, M).
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
 pub struct Zn<const M: u32>(u32);
 
+/// What Zn and the other modular integer types have in common, to write code
+/// that works with any of them.
+pub trait ModInt:
+    Copy + Default + Eq + Hash + Debug + Display + FromStr + From<i64>
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign + MulAssign + DivAssign + Sum + Product
+{
+    fn modulus() -> u64;
+    /// Residue in [0, modulus).
+    fn value(self) -> u64;
+    fn pow(self, e: u64) -> Self;
+    /// Panics if gcd(self, modulus) != 1.
+    fn inverse(self) -> Self;
+    /// None if gcd(self, modulus) != 1.
+    fn checked_inverse(self) -> Option<Self> {
+        mod_inverse(self.value() as i64, Self::modulus() as i64).map(Self::from)
+    }
+}
+
+/// The operators and traits every modular integer type shares, for a type with
+/// From<i64>, Add, Sub, Mul, Neg and the inherent value and inverse: From the
+/// other integers, Div, x op= y, the operators mixed with integers both ways,
+/// Sum, Product, Display, Debug and FromStr. The modulus is an expression,
+/// modint_impls!([const M: u32] Zn<M>, M), with [] for a type without generics.
+macro_rules! modint_impls {
+    ($gen:tt $t:ty, $m:expr) => {
+        $crate::zn::modint_impls!(@from $gen $t, $m, i32 i128 isize u32 u64 usize);
+        $crate::zn::modint_impls!(@same $gen $t, Add add AddAssign add_assign, Sub sub SubAssign sub_assign,
+            Mul mul MulAssign mul_assign, Div div DivAssign div_assign);
+        $crate::zn::modint_impls!(@mixed $gen $t, i32 i64 i128 isize u32 u64 usize);
+        $crate::zn::modint_impls!(@rest $gen $t);
+    };
+    (@from $gen:tt $t:ty, $m:expr, $($int:ty)*) => {$(
+        $crate::zn::modint_impls!(@from_int $gen $t, $m, $int);
+    )*};
+    (@from_int [$($g:tt)*] $t:ty, $m:expr, $int:ty) => {
+        impl<$($g)*> From<$int> for $t {
+            fn from(x: $int) -> $t { <$t>::from((x as i128).rem_euclid($m as i128) as i64) }
+        }
+    };
+    (@same $gen:tt $t:ty, $($op:ident $f:ident $op_assign:ident $f_assign:ident),*) => {$(
+        $crate::zn::modint_impls!(@assign $gen $t, $op $f $op_assign $f_assign);
+    )*};
+    (@assign [$($g:tt)*] $t:ty, $op:ident $f:ident $op_assign:ident $f_assign:ident) => {
+        impl<$($g)*> std::ops::$op_assign<$t> for $t {
+            fn $f_assign(&mut self, other: $t) { *self = std::ops::$op::$f(*self, other); }
+        }
+    };
+    (@mixed $gen:tt $t:ty, $($int:ty)*) => {$(
+        $crate::zn::modint_impls!(@int $gen $t, $int, Add add AddAssign add_assign);
+        $crate::zn::modint_impls!(@int $gen $t, $int, Sub sub SubAssign sub_assign);
+        $crate::zn::modint_impls!(@int $gen $t, $int, Mul mul MulAssign mul_assign);
+        $crate::zn::modint_impls!(@int $gen $t, $int, Div div DivAssign div_assign);
+    )*};
+    (@int [$($g:tt)*] $t:ty, $int:ty, $op:ident $f:ident $op_assign:ident $f_assign:ident) => {
+        impl<$($g)*> std::ops::$op<$int> for $t {
+            type Output = $t;
+            fn $f(self, other: $int) -> $t { std::ops::$op::$f(self, <$t>::from(other)) }
+        }
+        impl<$($g)*> std::ops::$op<$t> for $int {
+            type Output = $t;
+            fn $f(self, other: $t) -> $t { std::ops::$op::$f(<$t>::from(self), other) }
+        }
+        impl<$($g)*> std::ops::$op_assign<$int> for $t {
+            fn $f_assign(&mut self, other: $int) { *self = std::ops::$op::$f(*self, <$t>::from(other)); }
+        }
+    };
+    (@rest [$($g:tt)*] $t:ty) => {
+        impl<$($g)*> std::ops::Div<$t> for $t {
+            type Output = $t;
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, other: $t) -> $t { self * other.inverse() }
+        }
+
+        impl<$($g)*> std::iter::Sum for $t {
+            fn sum<I: Iterator<Item = $t>>(iter: I) -> $t { iter.fold(<$t>::from(0i64), |a, b| a + b) }
+        }
+
+        impl<'a, $($g)*> std::iter::Sum<&'a $t> for $t {
+            fn sum<I: Iterator<Item = &'a $t>>(iter: I) -> $t { iter.copied().sum() }
+        }
+
+        impl<$($g)*> std::iter::Product for $t {
+            fn product<I: Iterator<Item = $t>>(iter: I) -> $t { iter.fold(<$t>::from(1i64), |a, b| a * b) }
+        }
+
+        impl<'a, $($g)*> std::iter::Product<&'a $t> for $t {
+            fn product<I: Iterator<Item = &'a $t>>(iter: I) -> $t { iter.copied().product() }
+        }
+
+        impl<$($g)*> std::fmt::Display for $t {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.value())
+            }
+        }
+
+        impl<$($g)*> std::fmt::Debug for $t {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.value())
+            }
+        }
+
+        /// Decimal integers of any length, e.g. "-12" or a 10^5 digit number.
+        impl<$($g)*> std::str::FromStr for $t {
+            type Err = String;
+            fn from_str(s: &str) -> Result<$t, String> {
+                let (negative, digits) = match s.strip_prefix('-') {
+                    Some(digits) => (true, digits),
+                    None => (false, s.strip_prefix('+').unwrap_or(s)),
+                };
+                if digits.is_empty() {
+                    return Err(format!("Invalid number {:?}", s));
+                }
+                let mut x = <$t>::from(0i64);
+                for c in digits.chars() {
+                    let d = c.to_digit(10).ok_or_else(|| format!("Invalid number {:?}", s))?;
+                    x = x * 10 + d;
+                }
+                Ok(if negative { -x } else { x })
+            }
+        }
+    };
+}
+
+pub(crate) use modint_impls;
+
 /// Return (d, x, y) such that: a*x + b*y = d.
 /// And d is the greatest common divisor between a and b.
 pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
     if b == 0 { return (a, 1, 0); }
     let (d, y, x) = extended_gcd(b, a%b);
-    (d, x, y - ((a/b))*x)
+    (d, x, y - (a/b)*x)
+}
+
+/// x in [0, m) with a*x = 1 mod m, None if gcd(a, m) != 1. m >= 1.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (d, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if d != 1 { return None; }
+    Some(x.rem_euclid(m))
 }
 
 impl<const M: u32> Zn<M> {
@@ -16,21 +158,53 @@ impl<const M: u32> Zn<M> {
         Zn((((x%m)+m)%m) as u32)
     }
 
+    /// Residue in [0, M).
+    pub fn value(self) -> u32 {
+        self.0
+    }
+
+    /// Panics if gcd(self, M) != 1.
     pub fn inverse(self) -> Zn<M> {
-        let (d, x, _) = extended_gcd(self.0 as i64, M as i64);
-        debug_assert!(d == 1);
-        Zn::new(x)
+        Zn::new(mod_inverse(self.0 as i64, M as i64).expect("not invertible"))
+    }
+
+    /// self^e by repeated squaring, O(log e).
+    pub fn pow(self, mut e: u64) -> Zn<M> {
+        let mut base = self;
+        let mut result = Zn(1 % M);
+        while e > 0 {
+            if e & 1 == 1 { result *= base; }
+            base *= base;
+            e >>= 1;
+        }
+        result
     }
 }
 
+impl<const M: u32> ModInt for Zn<M> {
+    fn modulus() -> u64 { M as u64 }
+    fn value(self) -> u64 { self.0 as u64 }
+    fn pow(self, e: u64) -> Zn<M> { Zn::pow(self, e) }
+    fn inverse(self) -> Zn<M> { Zn::inverse(self) }
+}
+
+impl<const M: u32> From<i64> for Zn<M> {
+    fn from(x: i64) -> Zn<M> { Zn::new(x) }
+}
+
 impl<const M: u32> std::ops::Add<Zn<M>> for Zn<M> {
     type Output = Zn<M>;
-    fn add(self, other: Zn<M>) -> Zn<M> { Zn((self.0+other.0)%M) }
+    fn add(self, other: Zn<M>) -> Zn<M> {
+        let x = self.0 as u64 + other.0 as u64;
+        Zn((if x >= M as u64 { x - M as u64 } else { x }) as u32)
+    }
 }
 
 impl<const M: u32> std::ops::Sub<Zn<M>> for Zn<M> {
     type Output = Zn<M>;
-    fn sub(self, other: Zn<M>) -> Zn<M> { Zn((self.0+M-other.0)%M) }
+    fn sub(self, other: Zn<M>) -> Zn<M> {
+        Zn(if self.0 >= other.0 { self.0 - other.0 } else { (self.0 as u64 + M as u64 - other.0 as u64) as u32 })
+    }
 }
 
 impl<const M: u32> std::ops::Mul<Zn<M>> for Zn<M> {
@@ -40,11 +214,13 @@ impl<const M: u32> std::ops::Mul<Zn<M>> for Zn<M> {
     }
 }
 
-impl<const M: u32> std::ops::Div<Zn<M>> for Zn<M> {
+impl<const M: u32> std::ops::Neg for Zn<M> {
     type Output = Zn<M>;
-    fn div(self, other: Zn<M>) -> Zn<M> { self * other.inverse() }
+    fn neg(self) -> Zn<M> { Zn((M-self.0)%M) }
 }
 
+modint_impls!([const M: u32] Zn<M>, M);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,6 +233,17 @@ mod tests {
         assert_eq!(extended_gcd(18, 30), (6, 2, -1));
     }
 
+    #[test]
+    fn test_mod_inverse() {
+        for m in 1..50i64 {
+            for a in -60..60 {
+                let brute = (0..m).find(|x| (a * x).rem_euclid(m) == 1 % m);
+                assert_eq!(mod_inverse(a, m), brute);
+            }
+        }
+        assert_eq!(mod_inverse(3, 1_000_000_000_000_000_000), Some(666_666_666_666_666_667));
+    }
+
     #[test]
     fn test_zn() {
         const MOD: u32 = 1000000007;
@@ -87,4 +274,62 @@ mod tests {
         assert_eq!((p20/n10).0, MOD-2);
         assert_eq!((Z::new(120)/Z::new(6)).0, 20);
     }
+
+    #[test]
+    fn test_zn_ops() {
+        type Z = Zn<998244353>;
+        let a = Z::new(3);
+        assert_eq!(a.pow(0), Z::new(1));
+        assert_eq!(a.pow(5), Z::new(243));
+        assert_eq!(a.pow(998244352), Z::new(1));
+        assert_eq!(Zn::<1>::new(5).pow(0).value(), 0);
+        assert_eq!(-a, Z::new(-3));
+        assert_eq!(-Z::new(0), Z::new(0));
+
+        let mut x = Z::new(10);
+        x += Z::new(5);
+        x -= 20;
+        assert_eq!(x, Z::new(-5));
+        x *= 4u64;
+        x /= Z::new(-2);
+        assert_eq!(x.value(), 10);
+        assert_eq!(2 * x + 1, Z::new(21));
+        assert_eq!(x - 11usize, Z::new(-1));
+        assert_eq!(1 / Z::new(2) * 4i32, Z::new(2));
+        assert_eq!(Z::from(u64::MAX), Z::new(((u64::MAX as u128) % 998244353) as i64));
+
+        let v: Vec<Z> = (1..=5).map(Z::new).collect();
+        assert_eq!(v.iter().sum::<Z>(), Z::new(15));
+        assert_eq!(v.iter().product::<Z>(), Z::new(120));
+        assert_eq!(v.into_iter().map(|x| x * x).sum::<Z>(), Z::new(55));
+
+        assert_eq!(format!("{} {:?}", Z::new(-1), [a, a]), "998244352 [3, 3]");
+        assert_eq!("-12".parse::<Z>(), Ok(Z::new(-12)));
+        assert_eq!("123456789123456789".parse::<Z>(), Ok(Z::from(123456789123456789i64)));
+        assert!("12a".parse::<Z>().is_err());
+        assert!("-".parse::<Z>().is_err());
+
+        type Big = Zn<4294967291>;
+        assert_eq!(Big::new(-1) + Big::new(-2), Big::new(-3));
+        assert_eq!(Big::new(1) - Big::new(-1), Big::new(2));
+
+        let set: std::collections::HashSet<Z> = [Z::new(1), Z::new(998244354)].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_zn_inverse() {
+        type Z = Zn<10>;
+        assert_eq!(Z::new(3).inverse(), Z::new(7));
+        assert_eq!(ModInt::checked_inverse(Z::new(3)), Some(Z::new(7)));
+        assert_eq!(ModInt::checked_inverse(Z::new(4)), None);
+        assert_eq!(ModInt::checked_inverse(Z::new(0)), None);
+        assert_eq!(ModInt::checked_inverse(Zn::<1>::new(0)), Some(Zn::new(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "not invertible")]
+    fn test_zn_inverse_not_invertible() {
+        Zn::<10>::new(4).inverse();
+    }
 }
@@ -0,0 +1,114 @@
+// ----- Integers modulo a 64-bit M -----
+// Same API as zn::Zn for moduli that don't fit in a u32, e.g. 10^18 + 9.
+// Products go through u128. M < 2^63, so that sums don't overflow.
+use crate::zn::{mod_inverse, modint_impls, ModInt};
+
+/// Integers modulo M, the value is always in [0, M).
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Zn64<const M: u64>(u64);
+
+impl<const M: u64> Zn64<M> {
+    /// Fails the build for a bad M, wherever From<i64> is used.
+    const CHECK: () = assert!(M < 1 << 63, "M must be below 2^63");
+
+    pub fn new(x: impl Into<i64>) -> Zn64<M> {
+        Zn64::from(x.into())
+    }
+
+    /// Residue in [0, M).
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Panics if gcd(self, M) != 1.
+    pub fn inverse(self) -> Zn64<M> {
+        Zn64::new(mod_inverse(self.0 as i64, M as i64).expect("not invertible"))
+    }
+
+    /// self^e by repeated squaring, O(log e).
+    pub fn pow(self, mut e: u64) -> Zn64<M> {
+        let mut base = self;
+        let mut result = Zn64(1 % M);
+        while e > 0 {
+            if e & 1 == 1 { result *= base; }
+            base *= base;
+            e >>= 1;
+        }
+        result
+    }
+}
+
+impl<const M: u64> ModInt for Zn64<M> {
+    fn modulus() -> u64 { M }
+    fn value(self) -> u64 { self.0 }
+    fn pow(self, e: u64) -> Zn64<M> { Zn64::pow(self, e) }
+    fn inverse(self) -> Zn64<M> { Zn64::inverse(self) }
+}
+
+impl<const M: u64> From<i64> for Zn64<M> {
+    fn from(x: i64) -> Zn64<M> {
+        let () = Zn64::<M>::CHECK;
+        Zn64(x.rem_euclid(M as i64) as u64)
+    }
+}
+
+impl<const M: u64> std::ops::Add<Zn64<M>> for Zn64<M> {
+    type Output = Zn64<M>;
+    fn add(self, other: Zn64<M>) -> Zn64<M> {
+        let x = self.0 + other.0;
+        Zn64(if x >= M { x - M } else { x })
+    }
+}
+
+impl<const M: u64> std::ops::Sub<Zn64<M>> for Zn64<M> {
+    type Output = Zn64<M>;
+    fn sub(self, other: Zn64<M>) -> Zn64<M> {
+        Zn64(if self.0 >= other.0 { self.0 - other.0 } else { self.0 + M - other.0 })
+    }
+}
+
+impl<const M: u64> std::ops::Mul<Zn64<M>> for Zn64<M> {
+    type Output = Zn64<M>;
+    fn mul(self, other: Zn64<M>) -> Zn64<M> {
+        Zn64((self.0 as u128 * other.0 as u128 % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> std::ops::Neg for Zn64<M> {
+    type Output = Zn64<M>;
+    fn neg(self) -> Zn64<M> { Zn64(0) - self }
+}
+
+modint_impls!([const M: u64] Zn64<M>, M);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zn64() {
+        const MOD: u64 = 1_000_000_000_000_000_009;
+        type Z = Zn64<MOD>;
+        let a = Z::new(-10);
+        let b = Z::from(999_999_999_999_999_999u64);
+        assert_eq!(a.value(), MOD - 10);
+        assert_eq!(a + b, Z::new(-20));
+        assert_eq!(a - b, Z::new(0));
+        assert_eq!(a * b, Z::new(100));
+        assert_eq!((b * b.inverse()).value(), 1);
+        assert_eq!(b / a, Z::new(1));
+        assert_eq!(-a, Z::new(10));
+        // Fermat's little theorem, MOD is prime
+        assert_eq!(Z::new(123456789).pow(MOD - 1), Z::new(1));
+        assert_eq!(Z::new(2).pow(64), Z::from(u64::MAX) + 1);
+
+        let mut x = Z::new(7);
+        x *= 3;
+        x -= Z::new(1);
+        x /= 4u64;
+        assert_eq!(x, Z::new(5));
+        assert_eq!(format!("{} {:?}", a, [x]), "999999999999999999 [5]");
+        assert_eq!("-10".parse::<Z>(), Ok(a));
+        assert_eq!((1..=20).map(Z::new).product::<Z>(), Z::new(2432902008176640000i64));
+    }
+}
@@ -0,0 +1,271 @@
+// ----- Split a token stream into top level items -----
+use crate::lexer::{Kind, Token};
+
+pub struct Item<'a> {
+    /// Index of the first token, leading comments (docs) included.
+    pub first: usize,
+    /// Index of the last token, inclusive.
+    pub last: usize,
+    /// Outer attributes without whitespace, e.g. "#[cfg(test)]".
+    pub attrs: Vec<String>,
+    /// "fn", "struct", "impl", "use", "macro_rules", ... or "" if unknown.
+    pub keyword: &'a str,
+    pub name: Option<&'a str>,
+    /// Index of the opening `{` of the body, if any.
+    pub body: Option<usize>,
+    /// `impl Trait for Type` or `impl Type`, last segment of the paths.
+    pub impl_trait: Option<&'a str>,
+    pub impl_type: Option<&'a str>,
+}
+
+impl Item<'_> {
+    fn unknown() -> Self {
+        Item {
+            first: 0,
+            last: 0,
+            attrs: vec![],
+            keyword: "",
+            name: None,
+            body: None,
+            impl_trait: None,
+            impl_type: None,
+        }
+    }
+
+    pub fn has_attr(&self, attr: &str) -> bool {
+        self.attrs.iter().any(|a| a == attr)
+    }
+
+    pub fn start(&self, tokens: &[Token]) -> usize {
+        tokens[self.first].start
+    }
+
+    pub fn end(&self, tokens: &[Token]) -> usize {
+        tokens[self.last].end()
+    }
+}
+
+const KEYWORDS: [&str; 13] = [
+    "fn", "struct", "enum", "union", "trait", "impl", "mod", "use", "const", "static", "type",
+    "macro_rules", "extern",
+];
+
+/// Index of the next non trivia token in [i, to).
+pub fn next_solid(tokens: &[Token], mut i: usize, to: usize) -> Option<usize> {
+    while i < to && tokens[i].is_trivia() { i += 1; }
+    if i < to { Some(i) } else { None }
+}
+
+fn is_open(t: &Token) -> bool {
+    t.is_punct("(") || t.is_punct("[") || t.is_punct("{")
+}
+
+fn is_close(t: &Token) -> bool {
+    t.is_punct(")") || t.is_punct("]") || t.is_punct("}")
+}
+
+/// Index of the delimiter that closes the one at i.
+pub fn matching(tokens: &[Token], i: usize) -> usize {
+    debug_assert!(is_open(&tokens[i]));
+    let mut depth = 0;
+    for (j, t) in tokens.iter().enumerate().skip(i) {
+        if is_open(t) { depth += 1; }
+        if is_close(t) {
+            depth -= 1;
+            if depth == 0 { return j; }
+        }
+    }
+    tokens.len() - 1
+}
+
+/// Index of the first `;` outside of any delimiter, starting at i.
+fn semicolon(tokens: &[Token], mut i: usize, to: usize) -> usize {
+    while i < to && !tokens[i].is_punct(";") {
+        if is_open(&tokens[i]) { i = matching(tokens, i); }
+        i += 1;
+    }
+    i.min(to - 1)
+}
+
+/// Index of the token that ends the item whose keyword is at i.
+fn item_last(tokens: &[Token], keyword: &str, i: usize, to: usize) -> usize {
+    match keyword {
+        "use" | "const" | "static" | "type" | "extern" => semicolon(tokens, i, to),
+        "macro_rules" => {
+            let mut j = i;
+            while j < to && !is_open(&tokens[j]) { j += 1; }
+            let close = matching(tokens, j);
+            if tokens[j].is_punct("{") { return close; }
+            next_solid(tokens, close + 1, to).unwrap_or(close)
+        }
+        _ => {
+            let mut j = i;
+            while j < to && !tokens[j].is_punct(";") && !tokens[j].is_punct("{") {
+                if tokens[j].is_punct("(") || tokens[j].is_punct("[") { j = matching(tokens, j); }
+                j += 1;
+            }
+            if j < to && tokens[j].is_punct("{") { matching(tokens, j) } else { j.min(to - 1) }
+        }
+    }
+}
+
+/// Trait and type of an impl header in [from, to), i.e. after the `impl`
+/// keyword and up to the body. Generic arguments are skipped, so
+/// `impl<const M: u32> std::ops::Add<Zn<M>> for Zn<M>` gives (Add, Zn).
+fn impl_header<'a>(tokens: &[Token<'a>], from: usize, to: usize) -> (Option<&'a str>, Option<&'a str>) {
+    let mut depth = 0;
+    let mut before_for = None;
+    let mut last = None;
+    let mut i = from;
+    while i < to {
+        let t = &tokens[i];
+        if t.is_punct("-") && tokens.get(i + 1).is_some_and(|t| t.is_punct(">")) {
+            i += 2;
+            continue;
+        }
+        if t.is_punct("<") { depth += 1; }
+        if t.is_punct(">") { depth -= 1; }
+        if is_open(t) { i = matching(tokens, i); }
+        if depth == 0 && t.kind == Kind::Ident {
+            match t.text {
+                "where" => break,
+                "for" => before_for = last.take(),
+                "dyn" | "mut" => {},
+                _ => last = Some(t.text),
+            }
+        }
+        i += 1;
+    }
+    (before_for, last)
+}
+
+/// Items in the token range [from, to). Anything that is not recognized as an
+/// item (e.g. a stray comment at the end) is left out.
+pub fn parse_items<'a>(tokens: &[Token<'a>], from: usize, to: usize) -> Vec<Item<'a>> {
+    let mut items = vec![];
+    let mut i = from;
+    loop {
+        while i < to && tokens[i].kind == Kind::Whitespace { i += 1; }
+        let first = i;
+        let Some(mut j) = next_solid(tokens, i, to) else { break };
+        let mut attrs = vec![];
+        let bang = next_solid(tokens, j + 1, to);
+        if tokens[j].is_punct("#") && let Some(bang) = bang && tokens[bang].is_punct("!") {
+            // inner attribute, e.g. `#![allow(dead_code)]`
+            let open = next_solid(tokens, bang + 1, to).unwrap();
+            let last = matching(tokens, open);
+            items.push(Item { first, last, attrs, keyword: "#!", ..Item::unknown() });
+            i = last + 1;
+            continue;
+        }
+        while tokens[j].is_punct("#") {
+            let open = next_solid(tokens, j + 1, to).unwrap();
+            let close = matching(tokens, open);
+            let attr: String = tokens[j..=close].iter()
+                .filter(|t| !t.is_trivia())
+                .map(|t| t.text)
+                .collect();
+            attrs.push(attr);
+            match next_solid(tokens, close + 1, to) {
+                Some(k) => j = k,
+                None => break,
+            }
+        }
+        // skip `pub(crate)`, `unsafe`, `async`, ... until the item keyword
+        let mut k = j;
+        while k < to && !(tokens[k].kind == Kind::Ident && KEYWORDS.contains(&tokens[k].text)) {
+            if tokens[k].is_punct("(") { k = matching(tokens, k); }
+            if tokens[k].is_punct("{") || tokens[k].is_punct(";") { break; }
+            k += 1;
+        }
+        if k >= to || tokens[k].kind != Kind::Ident {
+            // not an item we understand (e.g. a macro call), keep it whole
+            let last = if k < to && tokens[k].is_punct("{") { matching(tokens, k) } else { k.min(to - 1) };
+            items.push(Item { first, last, attrs, ..Item::unknown() });
+            i = last + 1;
+            continue;
+        }
+        let mut keyword = tokens[k].text;
+        if keyword == "const" {
+            // `const fn` is a function
+            if let Some(n) = next_solid(tokens, k + 1, to) && tokens[n].is_ident("fn") {
+                keyword = "fn";
+                k = n;
+            }
+        }
+        let name = match keyword {
+            "use" | "impl" | "extern" => None,
+            _ => {
+                let mut n = next_solid(tokens, k + 1, to);
+                if let Some(m) = n && tokens[m].is_punct("!") { n = next_solid(tokens, m + 1, to); }
+                n.filter(|&n| tokens[n].kind == Kind::Ident).map(|n| tokens[n].text)
+            }
+        };
+        let last = item_last(tokens, keyword, k, to);
+        let body = (k..=last).find(|&b| tokens[b].is_punct("{")).filter(|_| tokens[last].is_punct("}"));
+        let (impl_trait, impl_type) = match keyword {
+            "impl" => impl_header(tokens, k + 1, body.unwrap_or(last)),
+            _ => (None, None),
+        };
+        items.push(Item { first, last, attrs, keyword, name, body, impl_trait, impl_type });
+        i = last + 1;
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lexer::tokenize,
+    };
+
+    const SRC: &str = "\
+#![allow(dead_code)]
+use std::{io, fmt::Debug};
+
+/// Docs are part of the item.
+#[derive(Debug)]
+#[cfg(test)]
+pub struct A<T>(T);
+
+pub(crate) const fn f() -> u32 { 1 }
+const X: [u8; 2] = [1, 2];
+
+impl<const M: u32> std::ops::Add<Zn<M>> for Zn<M> where Zn<M>: Copy {
+    fn add(self, other: Zn<M>) -> Zn<M> { self }
+}
+impl<T: Fn() -> u32> A<T> {}
+
+macro_rules! m { () => {} }
+macro_rules! p ( () => {} );
+thread_local! { static Y: u32 = 1; }
+fn g<F: Fn(u32) -> u32>(f: F) -> u32 where F: Copy { f(1) }
+// a stray comment
+";
+
+    #[test]
+    fn test_parse_items() {
+        let tokens = tokenize(SRC);
+        let items = parse_items(&tokens, 0, tokens.len());
+        let text = |item: &Item| &SRC[item.start(&tokens)..item.end(&tokens)];
+        let summary: Vec<(&str, Option<&str>)> = items.iter().map(|item| (item.keyword, item.name)).collect();
+        assert_eq!(summary, [
+            ("#!", None), ("use", None), ("struct", Some("A")), ("fn", Some("f")), ("const", Some("X")),
+            ("impl", None), ("impl", None), ("macro_rules", Some("m")), ("macro_rules", Some("p")),
+            ("", None), ("fn", Some("g")),
+        ]);
+        assert_eq!(text(&items[2]), "/// Docs are part of the item.\n#[derive(Debug)]\n#[cfg(test)]\npub struct A<T>(T);");
+        assert_eq!(items[2].attrs, ["#[derive(Debug)]", "#[cfg(test)]"]);
+        assert!(items[2].has_attr("#[cfg(test)]"));
+        assert_eq!(text(&items[8]), "macro_rules! p ( () => {} );");
+        assert_eq!(text(&items[9]), "thread_local! { static Y: u32 = 1; }");
+        assert!(text(&items[10]).ends_with("{ f(1) }"));
+        assert_eq!((items[5].impl_trait, items[5].impl_type), (Some("Add"), Some("Zn")));
+        assert_eq!((items[6].impl_trait, items[6].impl_type), (None, Some("A")));
+        let body = items[5].body.unwrap();
+        let members = parse_items(&tokens, body + 1, items[5].last);
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].name, Some("add"));
+    }
+}
@@ -0,0 +1,260 @@
+// ----- Minimal Rust lexer -----
+// Good enough to find item boundaries and paths in notebook sources, it does
+// not try to validate anything.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Ident,
+    Lifetime,
+    Literal,
+    Punct,
+    Comment,
+    Whitespace,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Token<'a> {
+    pub kind: Kind,
+    pub text: &'a str,
+    pub start: usize,
+}
+
+impl Token<'_> {
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        self.kind == Kind::Comment || self.kind == Kind::Whitespace
+    }
+
+    pub fn is_punct(&self, c: &str) -> bool {
+        self.kind == Kind::Punct && self.text == c
+    }
+
+    pub fn is_ident(&self, s: &str) -> bool {
+        self.kind == Kind::Ident && self.text == s
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_alphabetic()
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self, n: usize) -> Option<char> {
+        self.src[self.pos..].chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat_while(&mut self, f: impl Fn(char) -> bool) {
+        while let Some(c) = self.peek(0) {
+            if !f(c) { break; }
+            self.bump();
+        }
+    }
+
+    fn block_comment(&mut self) {
+        // nested comments are allowed in Rust
+        let mut depth = 0;
+        while let Some(c) = self.bump() {
+            if c == '/' && self.peek(0) == Some('*') {
+                self.bump();
+                depth += 1;
+            } else if c == '*' && self.peek(0) == Some('/') {
+                self.bump();
+                depth -= 1;
+                if depth == 0 { return; }
+            }
+        }
+    }
+
+    /// Quoted string or char literal, the opening quote was already consumed.
+    fn quoted(&mut self, quote: char) {
+        while let Some(c) = self.bump() {
+            if c == '\\' {
+                self.bump();
+            } else if c == quote {
+                break;
+            }
+        }
+    }
+
+    /// Raw string literal `r#"..."#`, positioned at the `r`.
+    fn raw_string(&mut self) {
+        self.bump();
+        let mut hashes = 0;
+        while self.peek(0) == Some('#') {
+            self.bump();
+            hashes += 1;
+        }
+        self.bump(); // opening "
+        let closing = format!("\"{}", "#".repeat(hashes));
+        match self.src[self.pos..].find(&closing) {
+            Some(i) => self.pos += i + closing.len(),
+            None => self.pos = self.src.len(),
+        }
+    }
+
+    /// Whether the `#`s and `"` of a raw string start at the n-th next char.
+    fn is_raw_string_start(&self, mut n: usize) -> bool {
+        while self.peek(n) == Some('#') { n += 1; }
+        self.peek(n) == Some('"')
+    }
+
+    fn number(&mut self) {
+        let hex = self.src[self.pos..].starts_with("0x");
+        let mut prev = ' ';
+        while let Some(c) = self.peek(0) {
+            let exponent_sign = (c == '+' || c == '-') && (prev == 'e' || prev == 'E') && !hex;
+            let fraction = c == '.' && self.peek(1).is_some_and(|d| d.is_ascii_digit());
+            if !(is_ident_continue(c) || exponent_sign || fraction) { break; }
+            prev = c;
+            self.bump();
+        }
+    }
+
+    fn next_kind(&mut self) -> Kind {
+        let c = self.peek(0).unwrap();
+        let c1 = self.peek(1);
+        if c.is_whitespace() {
+            self.eat_while(char::is_whitespace);
+            return Kind::Whitespace;
+        }
+        if c == '/' && c1 == Some('/') {
+            self.eat_while(|c| c != '\n');
+            return Kind::Comment;
+        }
+        if c == '/' && c1 == Some('*') {
+            self.block_comment();
+            return Kind::Comment;
+        }
+        if c == '"' {
+            self.bump();
+            self.quoted('"');
+            return Kind::Literal;
+        }
+        if c == '\'' {
+            self.bump();
+            // 'a' and '\n' are chars, 'a and 'static are lifetimes
+            if self.peek(0) == Some('\\') || self.peek(1) == Some('\'') {
+                self.quoted('\'');
+                return Kind::Literal;
+            }
+            self.eat_while(is_ident_continue);
+            return Kind::Lifetime;
+        }
+        if c == 'r' && (c1 == Some('"') || c1 == Some('#')) && self.is_raw_string_start(1) {
+            self.raw_string();
+            return Kind::Literal;
+        }
+        if (c == 'b' || c == 'c') && c1 == Some('r') && self.is_raw_string_start(2) {
+            self.bump();
+            self.raw_string();
+            return Kind::Literal;
+        }
+        if (c == 'b' || c == 'c') && (c1 == Some('"') || c1 == Some('\'')) {
+            self.bump();
+            let quote = self.bump().unwrap();
+            self.quoted(quote);
+            return Kind::Literal;
+        }
+        if c == 'r' && c1 == Some('#') {
+            self.bump();
+            self.bump();
+            self.eat_while(is_ident_continue);
+            return Kind::Ident;
+        }
+        if is_ident_start(c) {
+            self.eat_while(is_ident_continue);
+            return Kind::Ident;
+        }
+        if c.is_ascii_digit() {
+            self.number();
+            return Kind::Literal;
+        }
+        self.bump();
+        Kind::Punct
+    }
+}
+
+pub fn tokenize(src: &str) -> Vec<Token<'_>> {
+    let mut lexer = Lexer { src, pos: 0 };
+    let mut tokens = vec![];
+    while lexer.pos < src.len() {
+        let start = lexer.pos;
+        let kind = lexer.next_kind();
+        tokens.push(Token { kind, text: &src[start..lexer.pos], start });
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tests::notebook_sources,
+        Kind::*,
+    };
+
+    /// Kind and text of the tokens that aren't whitespace.
+    fn lex(src: &str) -> Vec<(Kind, &str)> {
+        tokenize(src).iter().filter(|t| t.kind != Whitespace).map(|t| (t.kind, t.text)).collect()
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        assert_eq!(lex(r###"r#"a "b" c"# x"###), [(Literal, r###"r#"a "b" c"#"###), (Ident, "x")]);
+        assert_eq!(lex(r###"r"C:\" br##"#"##"###), [(Literal, r#"r"C:\""#), (Literal, r###"br##"#"##"###)]);
+        assert_eq!(lex(r#"b"\"" c"x" b'\''"#), [(Literal, r#"b"\"""#), (Literal, r#"c"x""#), (Literal, r"b'\''")]);
+        assert_eq!(lex("r#type r"), [(Ident, "r#type"), (Ident, "r")]);
+    }
+
+    #[test]
+    fn test_lifetimes_and_chars() {
+        assert_eq!(
+            lex("impl<'a> X<'a, '_> for &'static str"),
+            [(Ident, "impl"), (Punct, "<"), (Lifetime, "'a"), (Punct, ">"), (Ident, "X"), (Punct, "<"),
+             (Lifetime, "'a"), (Punct, ","), (Lifetime, "'_"), (Punct, ">"), (Ident, "for"), (Punct, "&"),
+             (Lifetime, "'static"), (Ident, "str")]
+        );
+        assert_eq!(lex(r"'a' '\n' '\'' 'x"), [(Literal, "'a'"), (Literal, r"'\n'"), (Literal, r"'\''"), (Lifetime, "'x")]);
+    }
+
+    #[test]
+    fn test_comments_and_numbers() {
+        assert_eq!(lex("a /* b /* c */ d */ e // f\ng"),
+            [(Ident, "a"), (Comment, "/* b /* c */ d */"), (Ident, "e"), (Comment, "// f"), (Ident, "g")]);
+        assert_eq!(lex("1e-9 0x1f 1.5f64 1..2"),
+            [(Literal, "1e-9"), (Literal, "0x1f"), (Literal, "1.5f64"), (Literal, "1"), (Punct, "."),
+             (Punct, "."), (Literal, "2")]);
+        assert_eq!(lex("0xe-1"), [(Literal, "0xe"), (Punct, "-"), (Literal, "1")]);
+    }
+
+    #[test]
+    fn test_tokens_cover_the_source() {
+        for src in notebook_sources() {
+            let tokens = tokenize(&src);
+            let mut pos = 0;
+            for t in &tokens {
+                assert_eq!(t.start, pos);
+                pos = t.end();
+            }
+            assert_eq!(pos, src.len());
+        }
+    }
+}
@@ -0,0 +1,391 @@
+mod items;
+mod lexer;
+mod minify;
+mod prune;
+
+use {
+    std::{
+        collections::BTreeMap,
+        env,
+        fs,
+        path::{Path, PathBuf},
+        process::{Command, ExitCode},
+    },
+    colored::Colorize,
+    clap::Parser,
+    items::parse_items,
+    lexer::{tokenize, Kind, Token},
+    minify::minify,
+    prune::prune,
+};
+
+const NOTEBOOK_SRC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/notebook/src");
+const NOTEBOOK: &str = "notebook";
+
+/// Bundle a solution and the notebook modules it uses into a single file.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about=None)]
+struct CommandLineArgs {
+    /// Workspace directory, the solution is read from `src/main.rs`.
+    #[arg(value_name="directory", default_value=".")]
+    directory: PathBuf,
+    /// Output file, relative to the workspace directory.
+    #[arg(short, long, default_value="submission.rs")]
+    output: PathBuf,
+    /// Directory with the notebook sources.
+    #[arg(long, default_value=NOTEBOOK_SRC)]
+    notebook: PathBuf,
+    /// Rust edition used to verify that the bundled file compiles.
+    #[arg(long, default_value="2024")]
+    edition: String,
+    /// Do not verify that the bundled file compiles.
+    #[arg(long)]
+    no_check: bool,
+    /// Keep the notebook items that the solution never reaches.
+    #[arg(long)]
+    keep_unused: bool,
+    /// Drop comments and whitespace from the notebook modules.
+    #[arg(long)]
+    minify: bool,
+    /// Source size limit of the judge, in bytes (Codeforces accepts up to 64KB).
+    #[arg(long, value_name="bytes", default_value_t=65536)]
+    size_limit: usize,
+}
+
+/// Warn when the submission is over this fraction of the size limit.
+const SIZE_WARNING: f64 = 0.9;
+
+fn read_file(file_path: &Path) -> Result<String, String> {
+    fs::read_to_string(file_path)
+        .map_err(|error| format!("Error reading file {:?}.\n{}", file_path, error))
+}
+
+/// Modules referenced through `root::module` paths, e.g. with root = "notebook":
+/// `use notebook::{zn::Zn, sieve::*}` references "zn" and "sieve".
+fn referenced_modules(tokens: &[Token], root: &str) -> Vec<String> {
+    let solid: Vec<&Token> = tokens.iter().filter(|t| !t.is_trivia()).collect();
+    let mut modules = vec![];
+    for i in 0..solid.len() {
+        let is_path = solid[i].is_ident(root)
+            && solid.get(i + 1).is_some_and(|t| t.is_punct(":"))
+            && solid.get(i + 2).is_some_and(|t| t.is_punct(":"));
+        if !is_path { continue; }
+        let Some(next) = solid.get(i + 3) else { continue };
+        if next.kind == Kind::Ident {
+            modules.push(next.text.to_string());
+        } else if next.is_punct("{") {
+            // first segment of every path in the group
+            let mut depth = 0;
+            let mut expect_module = true;
+            for t in &solid[i + 3..] {
+                if t.is_punct("{") { depth += 1; }
+                if t.is_punct("}") { depth -= 1; }
+                if depth == 0 { break; }
+                if depth == 1 && t.is_punct(",") {
+                    expect_module = true;
+                } else if expect_module && t.kind == Kind::Ident {
+                    if t.text != "self" { modules.push(t.text.to_string()); }
+                    expect_module = false;
+                }
+            }
+        }
+    }
+    modules
+}
+
+/// Byte ranges of the items in the token range [from, to) that a submission
+/// never needs, also looking into the bodies of impls, traits and modules.
+fn cfg_items(tokens: &[Token], from: usize, to: usize, ranges: &mut Vec<(usize, usize)>) {
+    for item in parse_items(tokens, from, to) {
+        if item.has_attr("#[cfg(test)]") || item.has_attr("#[cfg(feature=\"local\")]") {
+            ranges.push((item.start(tokens), item.end(tokens)));
+        } else if let Some(body) = item.body && matches!(item.keyword, "impl" | "trait" | "mod") {
+            cfg_items(tokens, body + 1, item.last, ranges);
+        }
+    }
+}
+
+/// Remove the items of a module that a submission never needs, tests and
+/// debugging helpers of the `local` feature.
+fn strip_cfg_items(src: &str) -> String {
+    let tokens = tokenize(src);
+    let mut ranges = vec![];
+    cfg_items(&tokens, 0, tokens.len(), &mut ranges);
+    let mut out = String::new();
+    let mut pos = 0;
+    for (start, end) in ranges {
+        out += src[pos..start].trim_end();
+        pos = end;
+    }
+    out += &src[pos..];
+    out
+}
+
+/// `crate::x` (and `$crate::x` in macros) becomes `crate::notebook::x`, as the
+/// notebook modules are nested in a `notebook` module of the submission.
+fn rewrite_crate_paths(src: &str) -> String {
+    let tokens = tokenize(src);
+    let mut out = String::new();
+    for (i, t) in tokens.iter().enumerate() {
+        out += t.text;
+        let is_path = t.is_ident("crate")
+            && tokens.get(i + 1).is_some_and(|t| t.is_punct(":"))
+            && tokens.get(i + 2).is_some_and(|t| t.is_punct(":"));
+        if is_path {
+            out += "::";
+            out += NOTEBOOK;
+        }
+    }
+    out
+}
+
+/// `#[macro_export]` macros live in the root of the notebook crate, e.g.
+/// `notebook::input!`. Returns the module that defines each of them.
+fn exported_macros(notebook_dir: &Path) -> Result<BTreeMap<String, String>, String> {
+    let mut macros = BTreeMap::new();
+    let entries = fs::read_dir(notebook_dir)
+        .map_err(|error| format!("Error reading directory {:?}.\n{}", notebook_dir, error))?;
+    for entry in entries.flatten() {
+        let file_path = entry.path();
+        let Some(module) = file_path.file_stem().and_then(|s| s.to_str()) else { continue };
+        if file_path.extension().is_none_or(|e| e != "rs") || module == "lib" { continue; }
+        let src = read_file(&file_path)?;
+        let tokens = tokenize(&src);
+        for item in parse_items(&tokens, 0, tokens.len()) {
+            if item.keyword == "macro_rules" && item.has_attr("#[macro_export]") {
+                macros.insert(item.name.unwrap().to_string(), module.to_string());
+            }
+        }
+    }
+    Ok(macros)
+}
+
+/// Within the submission `#[macro_export]` would move the macros to the crate
+/// root, where they clash with the `use notebook::input` of the solution.
+/// They are made visible through paths instead, `pub(crate) use input;`.
+fn unexport_macros(src: &str) -> String {
+    let tokens = tokenize(src);
+    let mut out = String::new();
+    let mut pos = 0;
+    for item in parse_items(&tokens, 0, tokens.len()) {
+        if item.keyword != "macro_rules" || !item.has_attr("#[macro_export]") { continue; }
+        let solid: Vec<usize> = (item.first..=item.last).filter(|&i| !tokens[i].is_trivia()).collect();
+        for w in solid.windows(4) {
+            if tokens[w[0]].is_punct("#") && tokens[w[2]].is_ident("macro_export") {
+                out += &src[pos..tokens[w[0]].start];
+                pos = tokens[w[3]].end();
+                break;
+            }
+        }
+        let end = item.end(&tokens);
+        out += &src[pos..end];
+        out += &format!("\npub(crate) use {};", item.name.unwrap());
+        pos = end;
+    }
+    out += &src[pos..];
+    out
+}
+
+/// Notebook modules needed by the solution, following the dependencies
+/// between modules. The sources are ready to be nested in the submission.
+fn resolve_modules(
+    notebook_dir: &Path,
+    solution: &str,
+    macros: &BTreeMap<String, String>
+) -> Result<BTreeMap<String, String>, String> {
+    let mut modules = BTreeMap::new();
+    let mut pending = referenced_modules(&tokenize(solution), NOTEBOOK);
+    while let Some(mut name) = pending.pop() {
+        if let Some(module) = macros.get(&name) { name = module.clone(); }
+        if modules.contains_key(&name) { continue; }
+        let file_path = notebook_dir.join(format!("{}.rs", name));
+        if !file_path.is_file() {
+            return Err(format!("Notebook module `{}` not found at {:?}", name, file_path));
+        }
+        let src = strip_cfg_items(&read_file(&file_path)?);
+        let tokens = tokenize(&src);
+        for dep in referenced_modules(&tokens, "crate") {
+            if macros.contains_key(&dep) || notebook_dir.join(format!("{}.rs", dep)).is_file() {
+                pending.push(dep);
+            }
+        }
+        modules.insert(name, rewrite_crate_paths(&unexport_macros(&src)));
+    }
+    Ok(modules)
+}
+
+/// Macros of the module that are still around, after pruning.
+fn defined_macros<'a>(src: &str, macros: &'a BTreeMap<String, String>) -> Vec<&'a str> {
+    let tokens = tokenize(src);
+    parse_items(&tokens, 0, tokens.len()).iter()
+        .filter(|item| item.keyword == "macro_rules")
+        .filter_map(|item| macros.get_key_value(item.name?))
+        .map(|(name, _)| name.as_str())
+        .collect()
+}
+
+fn bundle(solution: &str, modules: &BTreeMap<String, String>, macros: &BTreeMap<String, String>) -> String {
+    let mut out = String::from(solution.trim_end());
+    out += "\n";
+    if modules.is_empty() { return out; }
+    out += "\n// --------------- Notebook ---------------\n";
+    out += "#[allow(dead_code, unused_imports, unused_macros)]\n";
+    out += &format!("mod {} {{\n", NOTEBOOK);
+    for (name, src) in modules {
+        out += &format!("pub mod {} {{\n", name);
+        out += src.trim();
+        out += "\n}\n";
+        for m in defined_macros(src, macros) {
+            out += &format!("pub(crate) use {}::{};\n", name, m);
+        }
+    }
+    out += "}\n";
+    out
+}
+
+/// Compile the bundled file on its own, as the judge would.
+fn check(file_path: &Path, edition: &str) -> Result<(), String> {
+    println!("Checking that {:?} compiles on its own..", file_path);
+    let out_dir = env::temp_dir().join(format!("cp-bundle-{}", std::process::id()));
+    let output = Command::new("rustc")
+        .arg("--edition").arg(edition)
+        .arg("--crate-type").arg("bin")
+        .arg("--emit").arg("metadata")
+        .arg("--out-dir").arg(&out_dir)
+        .arg(file_path)
+        .output()
+        .map_err(|error| format!("Error running `rustc`: {}", error))?;
+    let _ = fs::remove_dir_all(&out_dir);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("The bundled file does not compile.\n{}", stderr));
+    }
+    Ok(())
+}
+
+fn run(args: &CommandLineArgs) -> Result<(), String> {
+    let main_file = args.directory.join("src").join("main.rs");
+    let solution = read_file(&main_file)?;
+    let macros = exported_macros(&args.notebook)?;
+    let mut modules = resolve_modules(&args.notebook, &solution, &macros)?;
+    let names: Vec<&str> = modules.keys().map(|s| s.as_str()).collect();
+    println!("Inlining notebook modules: {:?}", names);
+    if !args.keep_unused {
+        modules = prune(&solution, &modules);
+    }
+    if args.minify {
+        modules = modules.into_iter().map(|(name, src)| (name, minify(&src))).collect();
+    }
+    let output = bundle(&solution, &modules, &macros);
+    let output_file = args.directory.join(&args.output);
+    fs::write(&output_file, &output)
+        .map_err(|error| format!("Error writing file {:?}.\n{}", output_file, error))?;
+    println!("Wrote file {:?} ({} bytes)", output_file, output.len());
+    if !args.no_check {
+        check(&output_file, &args.edition)?;
+    }
+    check_size(output.len(), args.size_limit)
+}
+
+fn check_size(size: usize, limit: usize) -> Result<(), String> {
+    if size > limit {
+        return Err(format!("The submission has {} bytes, over the limit of {} bytes", size, limit));
+    }
+    if size as f64 > SIZE_WARNING * limit as f64 {
+        let msg = format!("Warning: the submission has {} bytes, close to the limit of {} bytes", size, limit);
+        println!("{}", msg.yellow().bold());
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = CommandLineArgs::parse();
+    if let Err(error) = run(&args) {
+        let msg = format!("Error bundling solution: {}", error);
+        eprintln!("{}", msg.bold().red());
+        return ExitCode::FAILURE;
+    }
+    println!("{}", "-> Submission ready!".green().bold());
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The sources of the notebook, to try the bundler on real code.
+    pub fn notebook_sources() -> Vec<String> {
+        let mut sources: Vec<String> = fs::read_dir(NOTEBOOK_SRC).unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    #[test]
+    fn test_referenced_modules() {
+        let src = "use notebook::{zn::Zn, sieve::*, self};\nuse notebook::scanner::Scanner;\nfn f() { notebook :: fenwick::Fenwick::new(1); }";
+        assert_eq!(referenced_modules(&tokenize(src), NOTEBOOK), ["zn", "sieve", "scanner", "fenwick"]);
+        assert_eq!(referenced_modules(&tokenize("use crate::zn::Zn;"), "crate"), ["zn"]);
+        assert!(referenced_modules(&tokenize("use std::io;"), NOTEBOOK).is_empty());
+    }
+
+    #[test]
+    fn test_strip_cfg_items() {
+        let src = "\
+pub fn keep() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test() {}
+}
+";
+        assert_eq!(strip_cfg_items(src), "pub fn keep() {}\n");
+        let src = "\
+impl A {
+    pub fn keep() {}
+
+    /// Only for the tests.
+    #[cfg(test)]
+    fn helper() {}
+}
+
+mod inner {
+    #[cfg(test)]
+    mod tests {}
+    fn keep() {}
+}
+
+#[cfg(feature = \"local\")]
+pub fn dump() {}
+";
+        let expected = "\
+impl A {
+    pub fn keep() {}
+}
+
+mod inner {
+    fn keep() {}
+}
+";
+        assert_eq!(strip_cfg_items(src), expected);
+    }
+
+    #[test]
+    fn test_rewrite_crate_paths() {
+        assert_eq!(rewrite_crate_paths("use crate::zn::Zn;"), "use crate::notebook::zn::Zn;");
+        assert_eq!(
+            rewrite_crate_paths("macro_rules! m { () => { $crate::zn::modint_impls!(); } }"),
+            "macro_rules! m { () => { $crate::notebook::zn::modint_impls!(); } }"
+        );
+        assert_eq!(rewrite_crate_paths("pub(crate) use m;"), "pub(crate) use m;");
+    }
+
+    #[test]
+    fn test_unexport_macros() {
+        let src = "/// Reads.\n#[macro_export]\nmacro_rules! input {\n    () => {};\n}\n\nmacro_rules! inner { () => {} }\n";
+        let expected = "/// Reads.\n\nmacro_rules! input {\n    () => {};\n}\npub(crate) use input;\n\nmacro_rules! inner { () => {} }\n";
+        assert_eq!(unexport_macros(src), expected);
+    }
+}
@@ -0,0 +1,275 @@
+// ----- Drop the notebook items the solution never reaches -----
+// Reachability is approximated by names: an item is kept when its name shows
+// up in the solution or in another kept item. It may keep too much (e.g. all
+// the `new` methods of kept types) but never too little. The module paths of
+// `use` items don't count, only the names they import, and imports from
+// outside the notebook (std) are always kept as traits like io::Read may only
+// be used through their methods.
+use {
+    std::collections::{BTreeMap, HashSet},
+    crate::{
+        items::{parse_items, Item},
+        lexer::{tokenize, Kind, Token},
+    },
+};
+
+struct Module<'a> {
+    src: &'a str,
+    tokens: Vec<Token<'a>>,
+    items: Vec<Item<'a>>,
+    kept: Vec<bool>,
+    /// Members (methods, associated consts, ...) of inherent impls.
+    members: Vec<Vec<Item<'a>>>,
+    kept_members: Vec<Vec<bool>>,
+}
+
+fn is_inherent_impl(item: &Item) -> bool {
+    item.keyword == "impl" && item.impl_trait.is_none() && item.body.is_some()
+}
+
+fn reach<'a>(reached: &mut HashSet<&'a str>, tokens: &[Token<'a>], from: usize, to: usize) {
+    for t in &tokens[from..=to] {
+        if t.kind == Kind::Ident { reached.insert(t.text); }
+    }
+}
+
+/// Names imported by the `use` item in [from, to]: the last segment of each
+/// path and the `as` renames, e.g. Zn and ModInt for `use crate::zn::{Zn, ModInt}`.
+fn imported_names<'a>(tokens: &[Token<'a>], from: usize, to: usize) -> Vec<&'a str> {
+    let solid: Vec<&Token<'a>> = tokens[from..=to].iter().filter(|t| !t.is_trivia()).collect();
+    (0..solid.len())
+        .filter(|&i| solid[i].kind == Kind::Ident && !solid.get(i + 1).is_some_and(|t| t.is_punct(":")))
+        .map(|i| solid[i].text)
+        .filter(|name| !matches!(*name, "use" | "pub" | "crate" | "self" | "super" | "as"))
+        .collect()
+}
+
+/// Whether some path of the `use` item starts outside the notebook, e.g.
+/// `use std::io::Read` or `use {std::ops::Add, crate::zn::Zn}`.
+fn imports_external(tokens: &[Token], item: &Item) -> bool {
+    let solid: Vec<&Token> = tokens[item.first..=item.last].iter().filter(|t| !t.is_trivia()).collect();
+    let Some(u) = solid.iter().position(|t| t.is_ident("use")) else { return false };
+    let path = &solid[u + 1..];
+    let is_external = |i: usize| path.get(i).is_some_and(|t| t.kind == Kind::Ident)
+        && !matches!(path[i].text, "crate" | "self" | "super")
+        && path.get(i + 1).is_some_and(|t| t.is_punct(":"));
+    if !path.first().is_some_and(|t| t.is_punct("{")) { return is_external(0); }
+    // a group, each path at depth 1 has its own root
+    let mut depth = 0;
+    for (i, t) in path.iter().enumerate() {
+        if t.is_punct("{") { depth += 1; }
+        if t.is_punct("}") { depth -= 1; }
+        let starts_path = depth == 1 && (t.is_punct("{") || t.is_punct(","));
+        if starts_path && is_external(i + 1) { return true; }
+    }
+    false
+}
+
+fn reach_item<'a>(reached: &mut HashSet<&'a str>, tokens: &[Token<'a>], item: &Item) {
+    match item.keyword {
+        "use" => reached.extend(imported_names(tokens, item.first, item.last)),
+        _ => reach(reached, tokens, item.first, item.last),
+    }
+}
+
+fn should_keep(item: &Item, tokens: &[Token], reached: &HashSet<&str>, local: &HashSet<&str>) -> bool {
+    let is_reached = |name: Option<&str>| match name {
+        Some(name) => !local.contains(name) || reached.contains(name),
+        None => true,
+    };
+    match item.keyword {
+        "use" => imports_external(tokens, item)
+            || tokens[item.first..=item.last].iter().any(|t| t.is_punct("*"))
+            || imported_names(tokens, item.first, item.last).iter().any(|name| reached.contains(name)),
+        "impl" => is_reached(item.impl_trait) && is_reached(item.impl_type),
+        _ => item.name.is_none_or(|name| reached.contains(name)),
+    }
+}
+
+impl<'a> Module<'a> {
+    fn new(src: &'a str) -> Module<'a> {
+        let tokens = tokenize(src);
+        let items = parse_items(&tokens, 0, tokens.len());
+        let members: Vec<Vec<Item>> = items.iter()
+            .map(|item| match item.body {
+                Some(body) if is_inherent_impl(item) => parse_items(&tokens, body + 1, item.last),
+                _ => vec![],
+            })
+            .collect();
+        let kept = vec![false; items.len()];
+        let kept_members = members.iter().map(|m| vec![false; m.len()]).collect();
+        Module { src, tokens, items, kept, members, kept_members }
+    }
+
+    /// Keep whatever became reachable, returns whether anything changed.
+    fn expand(&mut self, reached: &mut HashSet<&'a str>, local: &HashSet<&str>) -> bool {
+        let mut changed = false;
+        for (i, item) in self.items.iter().enumerate() {
+            if !self.kept[i] && should_keep(item, &self.tokens, reached, local) {
+                self.kept[i] = true;
+                changed = true;
+                match item.body {
+                    // the members are handled one by one below
+                    Some(body) if is_inherent_impl(item) => reach(reached, &self.tokens, item.first, body),
+                    _ => reach_item(reached, &self.tokens, item),
+                }
+            }
+            if !self.kept[i] { continue; }
+            for (j, member) in self.members[i].iter().enumerate() {
+                if !self.kept_members[i][j] && member.name.is_none_or(|name| reached.contains(name)) {
+                    self.kept_members[i][j] = true;
+                    changed = true;
+                    reach(reached, &self.tokens, member.first, member.last);
+                }
+            }
+        }
+        changed
+    }
+
+    fn pruned(&self) -> String {
+        let mut removed = vec![];
+        for (i, item) in self.items.iter().enumerate() {
+            if !self.kept[i] {
+                removed.push((item.start(&self.tokens), item.end(&self.tokens)));
+                continue;
+            }
+            for (j, member) in self.members[i].iter().enumerate() {
+                if !self.kept_members[i][j] {
+                    removed.push((member.start(&self.tokens), member.end(&self.tokens)));
+                }
+            }
+        }
+        removed.sort();
+        let mut out = String::new();
+        let mut pos = 0;
+        for (start, end) in removed {
+            out += self.src[pos..start].trim_end();
+            pos = end;
+        }
+        out += &self.src[pos..];
+        collapse_blank_lines(&out)
+    }
+}
+
+fn collapse_blank_lines(src: &str) -> String {
+    let mut out = String::new();
+    let mut blank = 0;
+    for line in src.lines() {
+        if line.trim().is_empty() {
+            blank += 1;
+            if blank > 1 { continue; }
+        } else {
+            blank = 0;
+        }
+        out += line.trim_end();
+        out += "\n";
+    }
+    out
+}
+
+/// Modules with only the items reachable from the solution.
+pub fn prune(solution: &str, modules: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    let mut parsed: Vec<Module> = modules.values().map(|src| Module::new(src)).collect();
+    let local: HashSet<&str> = parsed.iter()
+        .flat_map(|m| m.items.iter().filter_map(|item| item.name))
+        .collect();
+    let solution_tokens = tokenize(solution);
+    let mut reached = HashSet::new();
+    for item in parse_items(&solution_tokens, 0, solution_tokens.len()) {
+        reach_item(&mut reached, &solution_tokens, &item);
+    }
+    loop {
+        let mut changed = false;
+        for module in parsed.iter_mut() {
+            changed |= module.expand(&mut reached, &local);
+        }
+        if !changed { break; }
+    }
+    modules.keys().cloned().zip(parsed.iter().map(|m| m.pruned())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tests::notebook_sources,
+    };
+
+    fn prune_one(solution: &str, src: &str) -> String {
+        let modules = BTreeMap::from([("m".to_string(), src.to_string())]);
+        prune(solution, &modules).remove("m").unwrap()
+    }
+
+    fn solid(src: &str) -> Vec<&str> {
+        tokenize(src).iter().filter(|t| !t.is_trivia()).map(|t| t.text).collect()
+    }
+
+    #[test]
+    fn test_prune() {
+        let src = "\
+use std::collections::HashMap;
+
+pub struct A;
+
+impl A {
+    pub fn new() -> A { helper(); A }
+    pub fn unused() {}
+}
+
+impl Default for A {
+    fn default() -> A { A::new() }
+}
+
+pub struct B;
+
+impl Default for B {
+    fn default() -> B { B }
+}
+
+fn helper() {}
+
+pub fn m() -> HashMap<u32, u32> { HashMap::new() }
+";
+        let expected = "\
+use std::collections::HashMap;
+
+pub struct A;
+
+impl A {
+    pub fn new() -> A { helper(); A }
+}
+
+impl Default for A {
+    fn default() -> A { A::new() }
+}
+
+fn helper() {}
+";
+        // the module path of a use item doesn't keep the fn with the same name
+        assert_eq!(prune_one("use notebook::m::*;\nfn main() { A::default(); }", src), expected);
+        let expected = "use std::collections::HashMap;\n\npub fn m() -> HashMap<u32, u32> { HashMap::new() }\n";
+        assert_eq!(prune_one("use notebook::m::{m as f};\nfn main() { f(); }", src), expected);
+        assert_eq!(prune_one("fn main() {}", src), "use std::collections::HashMap;\n");
+    }
+
+    #[test]
+    fn test_prune_use() {
+        let src = "\
+use std::io::Read;
+use crate::a::{A, B};
+pub(crate) use m;
+use {std::ops::Add, crate::c::C};
+macro_rules! m { () => {} }
+";
+        assert_eq!(prune_one("fn main() {}", src), "use std::io::Read;\nuse {std::ops::Add, crate::c::C};\n");
+        assert_eq!(prune_one("fn main() { B; m!(); }", src), src);
+    }
+
+    #[test]
+    fn test_prune_keeps_the_tokens() {
+        // With every name reached nothing is dropped, and only whitespace may change
+        for src in notebook_sources() {
+            assert_eq!(solid(&prune_one(&src, &src)), solid(&src));
+        }
+    }
+}
@@ -13,6 +13,7 @@ use {
 
 const TEMPLATE_C: &str = include_str!("../../templates/main.c");
 const TEMPLATE_RUST: &str = include_str!("../../templates/main.rs");
+const NOTEBOOK_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/notebook");
 
 #[derive(Copy, Clone, Debug, Parser, ValueEnum)]
 pub enum Language {
@@ -49,8 +50,8 @@ impl CommandLineArgs {
 }
 
 fn ensure_dir(dir_path: &Path) -> Result<(), String> {
-    if let Err(error) = fs::create_dir_all(&dir_path) {
-        let msg = format!("Could not create directory {:?}.\n{}", dir_path, error.to_string());
+    if let Err(error) = fs::create_dir_all(dir_path) {
+        let msg = format!("Could not create directory {:?}.\n{}", dir_path, error);
         return Err(msg);
     }
     Ok(())
@@ -58,7 +59,7 @@ fn ensure_dir(dir_path: &Path) -> Result<(), String> {
 
 fn write_file(file_path: &Path, content: &str) -> Result<(), String> {
     if let Err(error) = fs::write(file_path, content) {
-        let msg = format!("Error writing file {:?}.\n{}", file_path, error.to_string());
+        let msg = format!("Error writing file {:?}.\n{}", file_path, error);
         return Err(msg);
     }
     println!("Wrote file {:?}", file_path);
@@ -87,7 +88,7 @@ fn read_cf_samples_from_console(data_dir: &Path) -> Result<(),String> {
         let line = match line {
             Ok(line) => line,
             Err(error) => {
-                let msg = format!("Error reading line: {}", error.to_string());
+                let msg = format!("Error reading line: {}", error);
                 eprintln!("{}", msg.red());
                 continue;
             }
@@ -125,6 +126,37 @@ fn read_cf_samples_from_console(data_dir: &Path) -> Result<(),String> {
     Ok(())
 }
 
+/// Run `cargo <args>` in the given directory.
+fn run_cargo(args: &[&str], dir: &Path) -> Result<(), String> {
+    let cargo_command = format!("cargo {}", args[0]);
+    let mut command = Command::new("cargo");
+    let command = command
+        .args(args)
+        .current_dir(dir)
+        .stderr(Stdio::inherit())
+        .stdout(Stdio::inherit());
+    let mut spanwed_command = match command.spawn() {
+        Ok(spanwed) => spanwed,
+        Err(error) => {
+            let msg = format!("Error running `{}`: {}", cargo_command, error);
+            return Err(msg);
+        }
+    };
+    match spanwed_command.wait() {
+        Ok(code) => {
+            if !code.success() {
+                let msg = format!("{} failed with status code {}", cargo_command, code);
+                return Err(msg);
+            }
+        },
+        Err(error) => {
+            let msg = format!("Error in `{}`: {}", cargo_command, error);
+            return Err(msg);
+        }
+    };
+    Ok(())
+}
+
 fn create_workspace(args: &CommandLineArgs) -> Result<(), String> {
     let msg = format!("Creating workspace {:?}. Language = {:?}", args.directory, args.language);
     println!("{}", msg.cyan());
@@ -132,7 +164,7 @@ fn create_workspace(args: &CommandLineArgs) -> Result<(), String> {
     if dir.is_file() {
         return Err(format!("The path {:?} already exit as a file", args.directory));
     }
-    ensure_dir(&dir)?;
+    ensure_dir(dir)?;
     match args.language {
         Language::C => {
             let main_file = dir.join("main.c");
@@ -140,36 +172,14 @@ fn create_workspace(args: &CommandLineArgs) -> Result<(), String> {
         },
         Language::Rust => {
             println!("Running cargo init for {:?}..", dir);
-            let mut command = Command::new("cargo");
-            let command = command
-                .arg("init")
-                .arg(&dir)
-                .stderr(Stdio::inherit())
-                .stdout(Stdio::inherit());
-            let mut spanwed_command = match command.spawn() {
-                Ok(spanwed) => spanwed,
-                Err(error) => {
-                    let msg = format!("Error running `cargo init`: {:?}", error.to_string());
-                    return Err(msg);
-                }
-            };
-            match spanwed_command.wait() {
-                Ok(code) => {
-                    if !code.success() {
-                        let msg = format!("cargo init failed with status code {}", code);
-                        return Err(msg);
-                    }
-                },
-                Err(error) => {
-                    let msg = format!("Error in `cargo init`: {:?}", error.to_string());
-                    return Err(msg);
-                }
-            };
+            run_cargo(&["init"], dir)?;
+            println!("Adding notebook dependency from {:?}..", NOTEBOOK_DIR);
+            run_cargo(&["add", "notebook", "--path", NOTEBOOK_DIR], dir)?;
             let main_file = dir.join("src").join("main.rs");
             write_file(&main_file, TEMPLATE_RUST)?;
         }
     }
-    read_cf_samples_from_console(&dir)?;
+    read_cf_samples_from_console(dir)?;
     Ok(())
 }
 
@@ -178,7 +188,7 @@ fn start_nvim(args: &CommandLineArgs) -> Result<(), String> {
         let msg = format!(
             "Could not change directory to {:?}: {}",
             args.directory,
-            error.to_string());
+            error);
         return Err(msg);
     }
     let mut nvim_command = Command::new("nvim");
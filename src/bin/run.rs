@@ -1,3 +1,114 @@
-fn main() {
-    todo!("implement run method");
+use {
+    std::{
+        fs::{self, File},
+        path::{Path, PathBuf},
+        process::{Command, ExitCode, Stdio},
+        time::Instant,
+    },
+    colored::Colorize,
+    clap::Parser
+};
+
+/// Run a solution on the test cases of its workspace, `<case>.in` -> `<case>.out`.
+/// Debugging helpers are on: the `local` feature of the notebook for Rust and
+/// the `LOCAL` macro for C.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about=None)]
+struct CommandLineArgs {
+    /// Workspace directory.
+    #[arg(value_name="directory", default_value=".")]
+    directory: PathBuf,
+    /// Test cases to run, e.g. `0 1`. All the `.in` files by default.
+    #[arg(value_name="cases")]
+    cases: Vec<String>,
+    /// Build with optimizations.
+    #[arg(long)]
+    release: bool,
+}
+
+fn run_command(command: &mut Command, name: &str) -> Result<(), String> {
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(error) => return Err(format!("Error running `{}`: {}", name, error)),
+    };
+    if !status.success() {
+        return Err(format!("{} failed with status code {}", name, status));
+    }
+    Ok(())
+}
+
+/// Build the solution, returns the command that runs it.
+fn build(args: &CommandLineArgs) -> Result<Command, String> {
+    let dir = &args.directory;
+    if dir.join("Cargo.toml").is_file() {
+        let mut cargo_args = vec!["--quiet", "--features", "notebook/local"];
+        if args.release { cargo_args.push("--release"); }
+        println!("Running cargo build in {:?}..", dir);
+        run_command(Command::new("cargo").arg("build").args(&cargo_args).current_dir(dir), "cargo build")?;
+        let mut command = Command::new("cargo");
+        command.arg("run").args(&cargo_args).current_dir(dir);
+        return Ok(command);
+    }
+    if dir.join("main.c").is_file() {
+        let optimization = if args.release { "-O2" } else { "-O0" };
+        println!("Compiling {:?}..", dir.join("main.c"));
+        run_command(
+            Command::new("gcc").args(["-DLOCAL", optimization, "-o", "main", "main.c"]).current_dir(dir),
+            "gcc")?;
+        return Ok(Command::new(dir.join("main")));
+    }
+    Err(format!("No Cargo.toml or main.c found in {:?}", dir))
+}
+
+/// Test cases with an input file in the workspace, sorted by name.
+fn find_cases(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => return Err(format!("Error reading directory {:?}: {}", dir, error)),
+    };
+    let mut cases: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".in").map(String::from))
+        .collect();
+    cases.sort();
+    Ok(cases)
+}
+
+fn run_case(command: &mut Command, dir: &Path, case: &str) -> Result<(), String> {
+    let input_path = dir.join(format!("{}.in", case));
+    let output_path = dir.join(format!("{}.out", case));
+    let input = File::open(&input_path)
+        .map_err(|error| format!("Error opening file {:?}: {}", input_path, error))?;
+    let output = File::create(&output_path)
+        .map_err(|error| format!("Error creating file {:?}: {}", output_path, error))?;
+    println!("{}", format!("Running test case {}", case).cyan());
+    let start = Instant::now();
+    run_command(command.stdin(input).stdout(output).stderr(Stdio::inherit()), "solution")?;
+    println!("-> Done in {}ms, output written to {:?}", start.elapsed().as_millis(), output_path);
+    Ok(())
+}
+
+fn run(args: &CommandLineArgs) -> Result<(), String> {
+    let mut command = build(args)?;
+    let cases = match args.cases.is_empty() {
+        true => find_cases(&args.directory)?,
+        false => args.cases.clone(),
+    };
+    if cases.is_empty() {
+        return Err(format!("No test cases found in {:?}", args.directory));
+    }
+    for case in &cases {
+        run_case(&mut command, &args.directory, case)?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = CommandLineArgs::parse();
+    if let Err(error) = run(&args) {
+        let msg = format!("Error running solution: {}", error);
+        eprintln!("{}", msg.bold().red());
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
 }